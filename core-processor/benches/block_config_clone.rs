@@ -0,0 +1,104 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Demonstrates the allocation win from passing `BlockConfig` by reference into `process()`
+//! instead of deep-cloning the whole thing per dispatch (see [`crate::processing::process`]).
+//!
+//! `BlockConfig` and its `Costs` table live in `configs.rs`, which isn't part of this crate
+//! slice, so this benchmark reconstructs the shape of the two fields `process()` actually needs
+//! to clone per call (`forbidden_funcs`, `costs`) at a representative size, rather than the real
+//! types. `whole_config_clone` stands in for the pre-change baseline of cloning everything the
+//! config owns; `shared_reference` is what `process()` does today.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::collections::HashSet;
+
+const DISPATCHES_PER_BLOCK: usize = 2_000;
+const FORBIDDEN_FUNCS_LEN: usize = 64;
+const COST_TABLE_LEN: usize = 128;
+
+#[derive(Clone)]
+struct CostsStandIn {
+    ext: Vec<u64>,
+    lazy_pages: Vec<u64>,
+    write: Vec<u64>,
+}
+
+#[derive(Clone)]
+struct BlockConfigStandIn {
+    forbidden_funcs: HashSet<u32>,
+    costs: CostsStandIn,
+    // Every other `BlockConfig` field `process()` only reads (never clones): `Copy` scalars like
+    // `block_info`, `existential_deposit`, `mailbox_threshold`, etc.
+    block_info: u64,
+    existential_deposit: u128,
+}
+
+fn sample_config() -> BlockConfigStandIn {
+    BlockConfigStandIn {
+        forbidden_funcs: (0..FORBIDDEN_FUNCS_LEN as u32).collect(),
+        costs: CostsStandIn {
+            ext: vec![1; COST_TABLE_LEN],
+            lazy_pages: vec![1; COST_TABLE_LEN],
+            write: vec![1; COST_TABLE_LEN],
+        },
+        block_info: 0,
+        existential_deposit: 0,
+    }
+}
+
+/// Baseline: one deep clone of the entire config per dispatch, as `process()` used to take
+/// `block_config: BlockConfig` by value.
+fn whole_config_clone(config: &BlockConfigStandIn) -> BlockConfigStandIn {
+    config.clone()
+}
+
+/// Current behavior: `process()` takes `&BlockConfig` and only clones the two fields
+/// `ExecutionSettings` needs to own.
+fn shared_reference_partial_clone(config: &BlockConfigStandIn) -> (HashSet<u32>, CostsStandIn) {
+    (config.forbidden_funcs.clone(), config.costs.clone())
+}
+
+fn bench_block_config_clone(c: &mut Criterion) {
+    let config = sample_config();
+
+    c.bench_function(
+        &format!("whole_config_clone x {DISPATCHES_PER_BLOCK} dispatches"),
+        |b| {
+            b.iter(|| {
+                for _ in 0..DISPATCHES_PER_BLOCK {
+                    criterion::black_box(whole_config_clone(&config));
+                }
+            })
+        },
+    );
+
+    c.bench_function(
+        &format!("shared_reference_partial_clone x {DISPATCHES_PER_BLOCK} dispatches"),
+        |b| {
+            b.iter(|| {
+                for _ in 0..DISPATCHES_PER_BLOCK {
+                    criterion::black_box(shared_reference_partial_clone(&config));
+                }
+            })
+        },
+    );
+}
+
+criterion_group!(benches, bench_block_config_clone);
+criterion_main!(benches);
@@ -34,7 +34,10 @@ use alloc::{
 use gear_core::{
     env::Externalities,
     ids::{prelude::*, MessageId, ProgramId},
-    message::{ContextSettings, DispatchKind, IncomingDispatch, ReplyMessage, StoredDispatch},
+    message::{
+        ContextSettings, DispatchKind, IncomingDispatch, Payload, ReplyMessage, StoredDispatch,
+        MAX_PAYLOAD_SIZE,
+    },
     reservation::GasReservationState,
 };
 use gear_core_backend::{
@@ -42,6 +45,7 @@ use gear_core_backend::{
     BackendExternalities,
 };
 use gear_core_errors::{ErrorReplyReason, SignalCode};
+use parity_scale_codec::Encode;
 
 /// Process program & dispatch for it and return journal for updates.
 pub fn process<Ext>(
@@ -58,20 +62,29 @@ where
 {
     use crate::precharge::SuccessfulDispatchResultKind::*;
 
-    let BlockConfig {
+    // `block_config` is shared across every dispatch in the block, so only the two genuinely
+    // heap-allocated pieces `ExecutionSettings` needs to own (`forbidden_funcs`, the relevant cost
+    // tables) are cloned here, not the whole config.
+    //
+    // TODO: `ExecutionSettings` itself still takes these by value (#3756-style follow-up); making
+    // it borrow `forbidden_funcs`/`costs` for the call's lifetime instead would drop these two
+    // clones too, but that requires a lifetime parameter on `ExecutionSettings`, which lives in
+    // `configs.rs` and is out of scope for this change.
+    let &BlockConfig {
         block_info,
         performance_multiplier,
-        forbidden_funcs,
+        ref forbidden_funcs,
         reserve_for,
         gas_multiplier,
-        costs,
+        ref costs,
         existential_deposit,
         mailbox_threshold,
         max_pages,
         outgoing_limit,
         outgoing_bytes_limit,
+        debug_mode,
         ..
-    } = block_config.clone();
+    } = block_config;
 
     let execution_settings = ExecutionSettings {
         block_info,
@@ -79,12 +92,13 @@ where
         existential_deposit,
         mailbox_threshold,
         max_pages,
-        ext_costs: costs.ext,
-        lazy_pages_costs: costs.lazy_pages,
-        forbidden_funcs,
+        ext_costs: costs.ext.clone(),
+        lazy_pages_costs: costs.lazy_pages.clone(),
+        forbidden_funcs: forbidden_funcs.clone(),
         reserve_for,
         random_data,
         gas_multiplier,
+        debug_mode,
     };
 
     let dispatch = execution_context.dispatch;
@@ -145,13 +159,15 @@ where
                 res.gas_amount.burned(),
                 res.system_reservation_context,
                 ActorExecutionErrorReplyReason::Trap(reason),
+                debug_mode,
+                res.debug_messages,
             ),
-            DispatchResultKind::Success => process_success(Success, res),
+            DispatchResultKind::Success => process_success(Success, res, debug_mode),
             DispatchResultKind::Wait(duration, ref waited_type) => {
-                process_success(Wait(duration, waited_type.clone()), res)
+                process_success(Wait(duration, waited_type.clone()), res, debug_mode)
             }
             DispatchResultKind::Exit(value_destination) => {
-                process_success(Exit(value_destination), res)
+                process_success(Exit(value_destination), res, debug_mode)
             }
             DispatchResultKind::GasAllowanceExceed => {
                 process_allowance_exceed(dispatch, program_id, res.gas_amount.burned())
@@ -163,6 +179,8 @@ where
             e.gas_amount.burned(),
             system_reservation_ctx,
             e.reason,
+            debug_mode,
+            e.debug_messages,
         )),
         Err(ExecutionError::System(e)) => Err(e),
     }
@@ -195,12 +213,101 @@ impl ProcessErrorCase {
     }
 }
 
+/// Set on the leading header byte of [`encode_error_reply`]'s output when `msg` didn't fit and had
+/// to be clipped.
+const ERROR_REPLY_TRUNCATED_FLAG: u8 = 0b1;
+
+/// Marker appended to the message when it had to be truncated to fit.
+const ERROR_REPLY_TRUNCATION_MARKER: &str = "…";
+
+/// Encodes an error reply payload as a small header — a flags byte followed by the SCALE-encoded
+/// `reason` — plus as much of `msg` as fits in the remaining space.
+///
+/// Never panics, unlike a naive `msg.into_bytes().try_into().unwrap()`: a `msg` that doesn't fit
+/// (a trap/panic string can be attacker-controlled) is truncated, with a trailing `…` marker and
+/// the header's truncated bit set, rather than blowing past [`Payload`]'s max size. The header
+/// always wins space over the message, so `reason` is recoverable even when `msg` is clipped to
+/// nothing.
+pub fn encode_error_reply(reason: ErrorReplyReason, msg: &str) -> Payload {
+    let mut header = Vec::with_capacity(1 + MAX_PAYLOAD_SIZE.min(32));
+    header.push(0);
+    header.extend_from_slice(&reason.encode());
+    // The reason code is only ever a handful of bytes; this is just a last-resort guard so a
+    // pathologically large reason still can't panic here.
+    header.truncate(MAX_PAYLOAD_SIZE);
+
+    let budget = MAX_PAYLOAD_SIZE - header.len();
+    let msg_bytes = msg.as_bytes();
+
+    let (body, truncated) = if msg_bytes.len() <= budget {
+        (msg_bytes.to_vec(), false)
+    } else if budget <= ERROR_REPLY_TRUNCATION_MARKER.len() {
+        (Vec::new(), true)
+    } else {
+        let mut cut = budget - ERROR_REPLY_TRUNCATION_MARKER.len();
+        // Don't split a UTF-8 character in half.
+        while cut > 0 && !msg.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        let mut body = msg_bytes[..cut].to_vec();
+        body.extend_from_slice(ERROR_REPLY_TRUNCATION_MARKER.as_bytes());
+        (body, true)
+    };
+
+    if truncated {
+        if let Some(flags) = header.first_mut() {
+            *flags |= ERROR_REPLY_TRUNCATED_FLAG;
+        }
+    }
+
+    header.extend(body);
+    header
+        .try_into()
+        .unwrap_or_else(|_| Payload::default())
+}
+
+/// Turns the raw debug payloads a program emitted (e.g. via a logging syscall) during execution
+/// into journal notes, or drops them entirely when `debug_mode` is off.
+///
+/// Debug output must never be able to influence consensus: on the production path (`debug_mode ==
+/// false`) this is a no-op, so nothing derived from `messages` ever reaches the journal. When
+/// enabled, each message is validated as UTF-8; one that isn't is lossily converted (replacing the
+/// invalid bytes rather than discarding the whole message) and prefixed with `[invalid utf-8] `,
+/// so it's still visible to whoever's debugging, distinguishable from a message that was valid
+/// all along.
+fn debug_message_notes(
+    message_id: MessageId,
+    debug_mode: bool,
+    messages: Vec<Vec<u8>>,
+) -> Vec<JournalNote> {
+    if !debug_mode {
+        return Vec::new();
+    }
+
+    messages
+        .into_iter()
+        .map(|bytes| {
+            let payload = String::from_utf8(bytes.clone()).unwrap_or_else(|_| {
+                log::debug!("Debug message {message_id} is not valid UTF-8, lossily converting");
+                alloc::format!("[invalid utf-8] {}", String::from_utf8_lossy(&bytes))
+            });
+
+            JournalNote::DebugMessage {
+                message_id,
+                payload: payload.into_bytes(),
+            }
+        })
+        .collect()
+}
+
 fn process_error(
     dispatch: IncomingDispatch,
     program_id: ProgramId,
     gas_burned: u64,
     system_reservation_ctx: SystemReservationContext,
     case: ProcessErrorCase,
+    debug_mode: bool,
+    debug_messages: Vec<Vec<u8>>,
 ) -> Vec<JournalNote> {
     let mut journal = Vec::new();
 
@@ -213,6 +320,8 @@ fn process_error(
         amount: gas_burned,
     });
 
+    journal.extend(debug_message_notes(message_id, debug_mode, debug_messages));
+
     // We check if value is greater than zero to don't provide
     // no-op journal note.
     //
@@ -253,12 +362,7 @@ fn process_error(
 
     if !dispatch.is_reply() && dispatch.kind() != DispatchKind::Signal {
         let (err, err_payload) = case.to_reason_and_payload();
-
-        // Panic is impossible, unless error message is too large or [Payload] max size is too small.
-        let err_payload = err_payload
-            .into_bytes()
-            .try_into()
-            .unwrap_or_else(|_| unreachable!("Error message is too large"));
+        let err_payload = encode_error_reply(err, &err_payload);
 
         // # Safety
         //
@@ -309,12 +413,15 @@ fn process_error(
 }
 
 /// Helper function for journal creation in trap/error case.
+#[allow(clippy::too_many_arguments)]
 pub fn process_execution_error(
     dispatch: IncomingDispatch,
     program_id: ProgramId,
     gas_burned: u64,
     system_reservation_ctx: SystemReservationContext,
     err: impl Into<ActorExecutionErrorReplyReason>,
+    debug_mode: bool,
+    debug_messages: Vec<Vec<u8>>,
 ) -> Vec<JournalNote> {
     process_error(
         dispatch,
@@ -322,6 +429,8 @@ pub fn process_execution_error(
         gas_burned,
         system_reservation_ctx,
         ProcessErrorCase::ExecutionFailed(err.into()),
+        debug_mode,
+        debug_messages,
     )
 }
 
@@ -334,12 +443,16 @@ pub fn process_reinstrumentation_error(
     let gas_burned = context.data.gas_counter.burned();
     let system_reservation_ctx = SystemReservationContext::from_dispatch(&dispatch);
 
+    // Re-instrumentation happens before the wasm program ever runs, so there's no debug output
+    // to surface.
     process_error(
         dispatch,
         program_id,
         gas_burned,
         system_reservation_ctx,
         ProcessErrorCase::ReinstrumentationFailed,
+        false,
+        Vec::new(),
     )
 }
 
@@ -354,12 +467,15 @@ pub fn process_non_executable(context: ContextChargedForProgram) -> Vec<JournalN
 
     let system_reservation_ctx = SystemReservationContext::from_dispatch(&dispatch);
 
+    // The message was never executed, so there's no debug output to surface.
     process_error(
         dispatch,
         destination_id,
         gas_counter.burned(),
         system_reservation_ctx,
         ProcessErrorCase::NonExecutable,
+        false,
+        Vec::new(),
     )
 }
 
@@ -367,6 +483,7 @@ pub fn process_non_executable(context: ContextChargedForProgram) -> Vec<JournalN
 pub fn process_success(
     kind: SuccessfulDispatchResultKind,
     dispatch_result: DispatchResult,
+    debug_mode: bool,
 ) -> Vec<JournalNote> {
     use crate::precharge::SuccessfulDispatchResultKind::*;
 
@@ -384,6 +501,7 @@ pub fn process_success(
         allocations,
         reply_deposits,
         reply_sent,
+        debug_messages,
         ..
     } = dispatch_result;
 
@@ -398,6 +516,8 @@ pub fn process_success(
         amount: gas_amount.burned(),
     });
 
+    journal.extend(debug_message_notes(message_id, debug_mode, debug_messages));
+
     if let Some(gas_reserver) = gas_reserver {
         journal.extend(gas_reserver.states().iter().flat_map(
             |(&reservation_id, &state)| match state {
@@ -570,3 +690,162 @@ pub fn process_allowance_exceed(
 
     journal
 }
+
+/// Runs the same `executor::execute_wasm` path as [`process`] to preview its effect — predicted
+/// gas, outgoing dispatches, page updates, and the final outcome — without committing to it.
+///
+/// Tooling (e.g. a try-runtime-style estimator) can use this to see what enqueuing a dispatch
+/// would do before actually enqueuing it, including auto-reply generation, system reservation
+/// unreserve, and wait/exit outcomes, without having to replay the returned journal against real
+/// storage. [`process`]'s own `process_success`/`process_error` journal builders are reused
+/// unchanged, so the preview stays byte-for-byte consistent with what real execution would
+/// produce; [`SpeculativeJournal`] only wraps and summarizes the result so it isn't mistaken for
+/// `process`'s committable output.
+pub fn process_speculative<Ext>(
+    block_config: &BlockConfig,
+    execution_context: ProcessExecutionContext,
+    random_data: (Vec<u8>, u32),
+) -> Result<SpeculativeJournal, SystemExecutionError>
+where
+    Ext: ProcessorExternalities + BackendExternalities + 'static,
+    <Ext as Externalities>::AllocError:
+        BackendAllocSyscallError<ExtError = Ext::UnrecoverableError>,
+    RunFallibleError: From<Ext::FallibleError>,
+    <Ext as Externalities>::UnrecoverableError: BackendSyscallError,
+{
+    let notes = process::<Ext>(block_config, execution_context, random_data)?;
+    Ok(SpeculativeJournal::from_notes(notes))
+}
+
+/// A preview of what [`process`] would have done, returned by [`process_speculative`].
+///
+/// Deliberately not a bare `Vec<JournalNote>` (what `process` itself returns): that would make it
+/// too easy for a caller to pass a speculative run's notes to whatever applies a real journal to
+/// storage. [`Self::notes`] exposes them for read-only inspection only.
+#[derive(Debug, Clone)]
+pub struct SpeculativeJournal {
+    notes: Vec<JournalNote>,
+    pub report: SpeculativeReport,
+}
+
+impl SpeculativeJournal {
+    fn from_notes(notes: Vec<JournalNote>) -> Self {
+        let report = SpeculativeReport::from_notes(&notes);
+        Self { notes, report }
+    }
+
+    /// The notes `process` would have produced, for inspection only.
+    pub fn notes(&self) -> &[JournalNote] {
+        &self.notes
+    }
+}
+
+/// Cheap-to-inspect summary of a [`process_speculative`] run, without having to walk the full
+/// journal.
+#[derive(Debug, Clone, Default)]
+pub struct SpeculativeReport {
+    /// Gas the dispatch would burn.
+    pub gas_burned: u64,
+    /// Number of outgoing dispatches (including any auto-generated success reply) it would send.
+    pub outgoing_dispatches: usize,
+    /// Number of memory pages it would write.
+    pub page_updates: usize,
+    /// Allocation count after execution, if allocations changed at all.
+    ///
+    /// This is the post-execution page count from the journal's `UpdateAllocations` note, not a
+    /// signed delta against the pre-execution set — computing the latter would mean capturing the
+    /// program's allocations before `execution_context` is consumed by `process`, which isn't
+    /// exposed from this module.
+    pub allocations_after: Option<usize>,
+    /// The dispatch outcome. `None` when [`Self::waited`] is set (a waiting dispatch has no
+    /// [`DispatchOutcome`] yet — it's still pending the message that wakes it) and when the gas
+    /// allowance ran out (the journal then only holds a `StopProcessing` note).
+    pub outcome: Option<DispatchOutcome>,
+    /// Set when the dispatch would go to the waitlist instead of producing an outcome.
+    pub waited: bool,
+}
+
+impl SpeculativeReport {
+    fn from_notes(notes: &[JournalNote]) -> Self {
+        let mut report = Self::default();
+
+        for note in notes {
+            match note {
+                JournalNote::GasBurned { amount, .. } => report.gas_burned += amount,
+                JournalNote::SendDispatch { .. } => report.outgoing_dispatches += 1,
+                JournalNote::UpdatePage { .. } => report.page_updates += 1,
+                JournalNote::UpdateAllocations { allocations, .. } => {
+                    report.allocations_after = Some(allocations.len());
+                }
+                JournalNote::WaitDispatch { .. } => report.waited = true,
+                JournalNote::MessageDispatched { outcome, .. } => {
+                    report.outcome = Some(outcome.clone());
+                }
+                _ => {}
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_error_reply_empty_message_keeps_reason_untruncated() {
+        let payload = encode_error_reply(ErrorReplyReason::InactiveActor, "");
+
+        assert_eq!(payload[0] & ERROR_REPLY_TRUNCATED_FLAG, 0);
+        assert_eq!(&payload[1..], &ErrorReplyReason::InactiveActor.encode()[..]);
+    }
+
+    #[test]
+    fn encode_error_reply_oversized_message_is_truncated_with_marker_and_flag() {
+        let msg = "x".repeat(MAX_PAYLOAD_SIZE * 2);
+
+        let payload = encode_error_reply(ErrorReplyReason::InactiveActor, &msg);
+
+        assert!(payload.len() <= MAX_PAYLOAD_SIZE);
+        assert_eq!(payload[0] & ERROR_REPLY_TRUNCATED_FLAG, ERROR_REPLY_TRUNCATED_FLAG);
+        assert!(core::str::from_utf8(&payload)
+            .expect("truncation only ever cuts on a char boundary")
+            .ends_with(ERROR_REPLY_TRUNCATION_MARKER));
+    }
+
+    #[test]
+    fn debug_message_notes_disabled_drops_messages_entirely() {
+        let notes = debug_message_notes(MessageId::default(), false, vec![b"hello".to_vec()]);
+
+        assert!(notes.is_empty());
+    }
+
+    #[test]
+    fn debug_message_notes_enabled_valid_utf8_is_passed_through() {
+        let notes = debug_message_notes(MessageId::default(), true, vec![b"hello".to_vec()]);
+
+        let [JournalNote::DebugMessage {
+            message_id,
+            payload,
+        }] = notes.as_slice()
+        else {
+            unreachable!("exactly one message was passed in")
+        };
+        assert_eq!(*message_id, MessageId::default());
+        assert_eq!(payload, b"hello");
+    }
+
+    #[test]
+    fn debug_message_notes_enabled_invalid_utf8_is_lossily_converted() {
+        let invalid = vec![0xff, 0xfe, 0xfd];
+
+        let notes = debug_message_notes(MessageId::default(), true, vec![invalid.clone()]);
+
+        let [JournalNote::DebugMessage { payload, .. }] = notes.as_slice() else {
+            unreachable!("exactly one message was passed in")
+        };
+        assert_ne!(payload, &invalid);
+        assert!(core::str::from_utf8(payload).is_ok());
+    }
+}
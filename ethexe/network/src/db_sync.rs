@@ -41,11 +41,64 @@ use parity_scale_codec::{Decode, Encode};
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     task::{Context, Poll},
+    time::{Duration, Instant},
 };
 use tokio::task::JoinHandle;
 
-const STREAM_PROTOCOL: StreamProtocol =
-    StreamProtocol::new(concat!("/ethexe/db-sync/", env!("CARGO_PKG_VERSION")));
+/// Every wire protocol version this node can speak, newest first.
+///
+/// [`Behaviour::new`] registers all of them with the inner [`request_response::Behaviour`], so two
+/// peers running different point releases still negotiate whichever entry they have in common via
+/// libp2p's usual multistream-select instead of the connection being torn down outright. The
+/// legacy `/1` protocol predates the per-release versioning scheme and is kept around purely as a
+/// floor for old peers; it should never be removed without a deprecation period.
+const SUPPORTED_PROTOCOLS: &[StreamProtocol] = &[
+    StreamProtocol::new(concat!("/ethexe/db-sync/", env!("CARGO_PKG_VERSION"))),
+    StreamProtocol::new("/ethexe/db-sync/1"),
+];
+
+/// Score every peer starts out with, before any [`RequestFailure`] is reported against it.
+const STARTING_REPUTATION: i32 = 100;
+
+/// Tunables for [`Behaviour`] that aren't already covered by the inner `request_response::Config`.
+#[derive(Debug, Clone)]
+pub(crate) struct Config {
+    /// Maximum number of distinct peers a single request is retried against before it's given up
+    /// on, see [`Behaviour::request`].
+    pub max_attempts: usize,
+    /// A peer is banned once its reputation score drops to or below this value.
+    pub ban_threshold: i32,
+    /// How long a ban lasts before the peer is allowed to connect again.
+    pub ban_cooldown: Duration,
+    /// Maximum number of inbound DB reads served concurrently. A request arriving once this many
+    /// are already in flight is refused outright rather than queued, see
+    /// [`Behaviour::handle_inner_event`].
+    pub max_concurrent_responses: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            ban_threshold: 0,
+            ban_cooldown: Duration::from_secs(5 * 60),
+            max_concurrent_responses: 8,
+        }
+    }
+}
+
+/// How much a single [`RequestFailure`] costs a peer's reputation score; a hash mismatch is
+/// deliberate data poisoning and costs the most, an absent response the least since that's often
+/// just a slow or offline peer rather than a faulty one.
+fn reputation_penalty(error: RequestFailure) -> i32 {
+    match error {
+        RequestFailure::DataHashMismatch => 50,
+        RequestFailure::ExcessiveData => 25,
+        RequestFailure::TypeMismatch => 20,
+        RequestFailure::HashInequality => 20,
+        RequestFailure::NoResponse => 5,
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RequestKind {
@@ -54,7 +107,7 @@ pub enum RequestKind {
     ProgramCodeIds,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum RequestFailure {
     /// Request kind unequal to response kind
     TypeMismatch,
@@ -64,23 +117,79 @@ pub enum RequestFailure {
     ExcessiveData,
     /// Hashed data unequal to its corresponding hash
     DataHashMismatch,
+    /// The peer didn't answer at all: outbound failure (timeout, connection drop, protocol
+    /// mismatch with that specific peer, ...)
+    NoResponse,
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub struct RequestId(u64);
 
+/// Identifies a [`Behaviour::sync_data`] bulk-fetch session.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct SessionId(u64);
+
+/// Caps a single response page, so a `DataForHashes`/`BlockEndProgramStates` answer covering a lot
+/// of state doesn't blow past request_response's message size limit in one frame. The responder
+/// (see [`Behaviour::read_db`]) always emits at least one item even if that item alone busts
+/// `max_bytes`, so a single oversized value can't stall pagination forever.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Encode, Decode)]
+pub struct Budget {
+    pub max_items: u32,
+    pub max_bytes: u32,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self {
+            max_items: 1024,
+            max_bytes: 1024 * 1024,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Encode, Decode)]
 pub enum Request {
-    BlockEndProgramStates(H256),
-    DataForHashes(BTreeSet<H256>),
+    BlockEndProgramStates {
+        block_hash: H256,
+        /// Resume after this actor, for a follow-up page of a
+        /// [`Response::BlockEndProgramStatesPartial`].
+        after: Option<ActorId>,
+        budget: Budget,
+    },
+    DataForHashes {
+        hashes: BTreeSet<H256>,
+        /// Resume after this hash, for a follow-up page of a
+        /// [`Response::DataForHashesPartial`].
+        after: Option<H256>,
+        budget: Budget,
+    },
     ProgramCodeIds(BTreeSet<ProgramId>),
 }
 
 impl Request {
+    /// Ask for every program's end-of-block state in `block_hash`, within the default [`Budget`].
+    pub fn block_end_program_states(block_hash: H256) -> Self {
+        Self::BlockEndProgramStates {
+            block_hash,
+            after: None,
+            budget: Budget::default(),
+        }
+    }
+
+    /// Ask for the data behind each of `hashes`, within the default [`Budget`].
+    pub fn data_for_hashes(hashes: BTreeSet<H256>) -> Self {
+        Self::DataForHashes {
+            hashes,
+            after: None,
+            budget: Budget::default(),
+        }
+    }
+
     fn kind(&self) -> RequestKind {
         match self {
-            Request::BlockEndProgramStates(_) => RequestKind::BlockEndProgramStates,
-            Request::DataForHashes(_) => RequestKind::DataForHashes,
+            Request::BlockEndProgramStates { .. } => RequestKind::BlockEndProgramStates,
+            Request::DataForHashes { .. } => RequestKind::DataForHashes,
             Request::ProgramCodeIds(_) => RequestKind::ProgramCodeIds,
         }
     }
@@ -88,11 +197,12 @@ impl Request {
     fn validate_response(&self, resp: &Response) -> Result<(), RequestFailure> {
         match (self, resp) {
             (
-                Request::BlockEndProgramStates(requested_block_hash),
-                Response::BlockEndProgramStates {
-                    block_hash,
-                    states: _,
+                Request::BlockEndProgramStates {
+                    block_hash: requested_block_hash,
+                    ..
                 },
+                Response::BlockEndProgramStates { block_hash, .. }
+                | Response::BlockEndProgramStatesPartial { block_hash, .. },
             ) => {
                 if requested_block_hash == block_hash {
                     Ok(())
@@ -100,8 +210,14 @@ impl Request {
                     Err(RequestFailure::HashInequality)
                 }
             }
-            (Request::DataForHashes(requested_hashes), Response::DataForHashes(hashes)) => {
-                for (hash, data) in hashes {
+            (
+                Request::DataForHashes {
+                    hashes: requested_hashes,
+                    ..
+                },
+                Response::DataForHashes(items) | Response::DataForHashesPartial { items, .. },
+            ) => {
+                for (hash, data) in items {
                     if !requested_hashes.contains(hash) {
                         return Err(RequestFailure::ExcessiveData);
                     }
@@ -135,8 +251,22 @@ pub enum Response {
         /// Program states for request block
         states: BTreeMap<ActorId, H256>,
     },
+    /// A budget-limited page of `BlockEndProgramStates`; [`Behaviour`] automatically requests
+    /// `next_cursor` onward from the same peer and reassembles the full map before emitting
+    /// [`Event::RequestSucceed`].
+    BlockEndProgramStatesPartial {
+        block_hash: H256,
+        states: BTreeMap<ActorId, H256>,
+        next_cursor: ActorId,
+    },
     /// Key (hash) - value (bytes) data
     DataForHashes(BTreeMap<H256, Vec<u8>>),
+    /// A budget-limited page of `DataForHashes`, continued the same way as
+    /// [`Response::BlockEndProgramStatesPartial`].
+    DataForHashesPartial {
+        items: BTreeMap<H256, Vec<u8>>,
+        next_cursor: H256,
+    },
     /// Program IDs and their corresponding code IDs
     ProgramCodeIds(BTreeMap<ProgramId, CodeId>),
 }
@@ -155,46 +285,218 @@ pub enum Event {
         peer_id: PeerId,
         /// The ID of request
         request_id: RequestId,
+        /// Protocol version the response was actually negotiated over, out of
+        /// [`SUPPORTED_PROTOCOLS`]
+        protocol: StreamProtocol,
         /// Response itself
         response: Response,
     },
     RequestFailed {
         /// The ID of request
         request_id: RequestId,
-        /// Reason of request failure
+        /// Reason the last attempt failed
         error: RequestFailure,
+        /// Every peer this request was tried against before it was given up on
+        peers_tried: BTreeSet<PeerId>,
+    },
+    /// Terminal event for a [`Behaviour::sync_data`] session: either every hash was fetched from
+    /// some peer, or every connected peer has been tried for whatever's left in `missing`.
+    SyncDataCompleted {
+        session_id: SessionId,
+        /// Hashes no connected peer could provide before the session gave up on them.
+        missing: BTreeSet<H256>,
     },
 }
 
 type InnerBehaviour = request_response::Behaviour<ParityScaleCodec<Request, Response>>;
 
+/// Rejection cause for a connection attempt from (or to) a peer currently serving out a ban.
+#[derive(Debug)]
+struct PeerBanned(PeerId);
+
+impl std::fmt::Display for PeerBanned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "peer {} is temporarily banned from db-sync for sending invalid data",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for PeerBanned {}
+
+/// A request still working its way through peer selection: which peers it's already been tried
+/// against, and what went wrong the last time.
+struct Attempt {
+    request: Request,
+    tried: HashSet<PeerId>,
+    last_error: Option<RequestFailure>,
+    /// Set while a [`Response::DataForHashesPartial`]/[`Response::BlockEndProgramStatesPartial`]
+    /// sequence is being reassembled against the peer it was dispatched to; `None` otherwise
+    /// (including for request kinds that are never paginated, like `ProgramCodeIds`).
+    pagination: Option<Pagination>,
+}
+
+/// Reassembly state for a paginated request, pinned to the peer it's currently in flight with:
+/// switching peers (a fresh dispatch via [`Behaviour::dispatch_pending`]) starts it over, since
+/// pages answered by two different peers have no guarantee of being consistent with each other.
+struct Pagination {
+    peer_id: PeerId,
+    acc: Accumulator,
+}
+
+enum Accumulator {
+    DataForHashes(BTreeMap<H256, Vec<u8>>),
+    BlockEndProgramStates {
+        block_hash: H256,
+        states: BTreeMap<ActorId, H256>,
+    },
+}
+
+/// A [`Behaviour::sync_data`] bulk-fetch in progress: a hash set sharded across several
+/// concurrent per-shard [`Request::DataForHashes`] requests, each tracked the same way a directly
+/// dispatched request would be.
+struct Session {
+    /// Hashes not currently out on the wire as part of some shard: either never sharded yet, or
+    /// bounced back after a shard failed or came back missing them.
+    residual: BTreeSet<H256>,
+    /// Shard request IDs currently in flight, each with the hashes it was asked for.
+    in_flight: HashMap<RequestId, BTreeSet<H256>>,
+    /// Peers a shard of this session has already failed against once; excluded from future
+    /// shards of the same session since they've already proven unable (or unwilling) to help it.
+    excluded_peers: HashSet<PeerId>,
+}
+
+/// How a session shard's underlying request resolved, as fed into [`Behaviour::complete_shard`].
+enum ShardOutcome {
+    Succeeded { peer_id: PeerId, response: Response },
+    Failed { peers_tried: BTreeSet<PeerId> },
+}
+
+impl Accumulator {
+    /// An empty accumulator matching `request`, or `None` if its kind is never paginated.
+    fn for_request(request: &Request) -> Option<Self> {
+        match request {
+            Request::DataForHashes { .. } => Some(Self::DataForHashes(BTreeMap::new())),
+            Request::BlockEndProgramStates { block_hash, .. } => {
+                Some(Self::BlockEndProgramStates {
+                    block_hash: *block_hash,
+                    states: BTreeMap::new(),
+                })
+            }
+            Request::ProgramCodeIds(_) => None,
+        }
+    }
+}
+
 pub(crate) struct Behaviour {
     inner: InnerBehaviour,
     connections: HashMap<PeerId, HashSet<ConnectionId>>,
     // requests
     request_id_counter: u64,
-    user_requests: VecDeque<(RequestId, Request)>,
-    ongoing_requests: HashMap<OutboundRequestId, (RequestId, Request)>,
+    /// Maximum number of distinct peers a single request will be tried against before
+    /// [`Event::RequestFailed`] is emitted for it.
+    max_attempts: usize,
+    /// Requests waiting for [`Self::poll`] to pick their next untried peer, in FIFO order.
+    pending_requests: VecDeque<RequestId>,
+    attempts: HashMap<RequestId, Attempt>,
+    ongoing_requests: HashMap<OutboundRequestId, RequestId>,
+    // sync sessions
+    session_id_counter: u64,
+    sessions: HashMap<SessionId, Session>,
+    /// Which session owns a shard request, so its `RequestSucceed`/`RequestFailed` can be folded
+    /// into the session instead of surfaced to the caller on its own.
+    session_requests: HashMap<RequestId, SessionId>,
+    /// [`Event::SyncDataCompleted`]s a session finalized outside of [`Self::poll`]'s own call into
+    /// [`Self::handle_inner_event`] (e.g. immediately in [`Self::sync_data`]), waiting their turn
+    /// to be emitted.
+    pending_session_events: VecDeque<Event>,
+    // reputation
+    ban_threshold: i32,
+    ban_cooldown: Duration,
+    reputation: HashMap<PeerId, i32>,
+    /// Peers banned until the paired `Instant`.
+    banned: HashMap<PeerId, Instant>,
+    /// Banned peers [`Self::poll`] still owes a `CloseConnection` event to.
+    pending_closes: VecDeque<PeerId>,
     // responses
     db: Database,
-    ongoing_response: Option<(
+    max_concurrent_responses: usize,
+    ongoing_responses: Vec<(
         request_response::ResponseChannel<Response>,
         JoinHandle<Response>,
     )>,
 }
 
 impl Behaviour {
-    pub fn new(cfg: request_response::Config, db: Database) -> Self {
+    pub fn new(protocol_cfg: request_response::Config, config: Config, db: Database) -> Self {
         Self {
-            inner: InnerBehaviour::new([(STREAM_PROTOCOL, ProtocolSupport::Full)], cfg),
+            inner: InnerBehaviour::new(
+                SUPPORTED_PROTOCOLS
+                    .iter()
+                    .cloned()
+                    .map(|protocol| (protocol, ProtocolSupport::Full)),
+                protocol_cfg,
+            ),
             connections: HashMap::new(),
             //
             request_id_counter: 0,
-            user_requests: VecDeque::new(),
+            max_attempts: config.max_attempts.max(1),
+            pending_requests: VecDeque::new(),
+            attempts: HashMap::new(),
             ongoing_requests: HashMap::new(),
             //
+            session_id_counter: 0,
+            sessions: HashMap::new(),
+            session_requests: HashMap::new(),
+            pending_session_events: VecDeque::new(),
+            //
+            ban_threshold: config.ban_threshold,
+            ban_cooldown: config.ban_cooldown,
+            reputation: HashMap::new(),
+            banned: HashMap::new(),
+            pending_closes: VecDeque::new(),
+            //
             db,
-            ongoing_response: None,
+            max_concurrent_responses: config.max_concurrent_responses.max(1),
+            ongoing_responses: Vec::new(),
+        }
+    }
+
+    /// Whether `peer_id` is currently serving out a ban handed down by [`Self::report`] or
+    /// [`Self::ban_peer`].
+    fn is_banned(&self, peer_id: &PeerId) -> bool {
+        self.banned
+            .get(peer_id)
+            .is_some_and(|&expires_at| Instant::now() < expires_at)
+    }
+
+    /// Ban `peer_id` for [`Config::ban_cooldown`], closing any connection already open with it.
+    pub fn ban_peer(&mut self, peer_id: PeerId) {
+        self.banned.insert(peer_id, Instant::now() + self.ban_cooldown);
+        self.pending_closes.push_back(peer_id);
+    }
+
+    /// Lift a ban on `peer_id` and reset its reputation score, ahead of the cooldown expiring on
+    /// its own.
+    pub fn unban_peer(&mut self, peer_id: &PeerId) {
+        self.banned.remove(peer_id);
+        self.reputation.remove(peer_id);
+    }
+
+    /// Dock `peer_id`'s reputation for `error`, banning it once the score crosses
+    /// [`Config::ban_threshold`]. Exposed so callers outside the db-sync request/response loop
+    /// (e.g. a higher-level sync protocol) can feed in their own judgments of a peer.
+    pub fn report(&mut self, peer_id: PeerId, error: RequestFailure) {
+        let score = self
+            .reputation
+            .entry(peer_id)
+            .or_insert(STARTING_REPUTATION);
+        *score -= reputation_penalty(error);
+
+        if *score <= self.ban_threshold {
+            self.ban_peer(peer_id);
         }
     }
 
@@ -205,23 +507,470 @@ impl Behaviour {
 
     pub fn request(&mut self, request: Request) -> RequestId {
         let request_id = self.next_request_id();
-        self.user_requests.push_back((request_id, request));
+        self.attempts.insert(
+            request_id,
+            Attempt {
+                request,
+                tried: HashSet::new(),
+                last_error: None,
+                pagination: None,
+            },
+        );
+        self.pending_requests.push_back(request_id);
         request_id
     }
 
+    /// Start a resilient, parallel bulk-fetch of `hashes`: the set is sharded across every
+    /// currently connected peer and each shard dispatched as its own `DataForHashes` request.
+    /// Whatever a shard's peer doesn't have (or fails on) bounces back and is re-sharded across
+    /// the peers that haven't already proven unable to help, until the whole set is collected or
+    /// every connected peer has been exhausted — either way ending in one
+    /// [`Event::SyncDataCompleted`].
+    pub fn sync_data(&mut self, hashes: BTreeSet<H256>) -> SessionId {
+        self.session_id_counter += 1;
+        let session_id = SessionId(self.session_id_counter);
+
+        self.sessions.insert(
+            session_id,
+            Session {
+                residual: hashes,
+                in_flight: HashMap::new(),
+                excluded_peers: HashSet::new(),
+            },
+        );
+
+        self.dispatch_session(session_id);
+        session_id
+    }
+
+    /// Shard `session_id`'s residual hashes across its currently eligible connected peers (those
+    /// not yet in `excluded_peers`) and dispatch one shard per peer, or finalize the session (via
+    /// [`Self::pending_session_events`]) if there's nothing left to try: an empty residual with
+    /// nothing in flight is success, a non-empty residual with no eligible peer and nothing in
+    /// flight means every peer has been exhausted.
+    fn dispatch_session(&mut self, session_id: SessionId) {
+        let session = self
+            .sessions
+            .get(&session_id)
+            .expect("session exists while being dispatched");
+        let in_flight_is_empty = session.in_flight.is_empty();
+
+        if session.residual.is_empty() {
+            if in_flight_is_empty {
+                self.sessions.remove(&session_id);
+                self.pending_session_events
+                    .push_back(Event::SyncDataCompleted {
+                        session_id,
+                        missing: BTreeSet::new(),
+                    });
+            }
+            return;
+        }
+
+        let eligible_peers: Vec<PeerId> = self
+            .connections
+            .keys()
+            .filter(|peer_id| !session.excluded_peers.contains(peer_id))
+            .copied()
+            .collect();
+
+        if eligible_peers.is_empty() {
+            if in_flight_is_empty {
+                let session = self
+                    .sessions
+                    .remove(&session_id)
+                    .expect("checked by get() above");
+                self.pending_session_events
+                    .push_back(Event::SyncDataCompleted {
+                        session_id,
+                        missing: session.residual,
+                    });
+            }
+            return;
+        }
+
+        let residual = std::mem::take(
+            &mut self
+                .sessions
+                .get_mut(&session_id)
+                .expect("checked by get() above")
+                .residual,
+        );
+        let shard_count = eligible_peers.len();
+        for (peer_id, shard) in eligible_peers
+            .into_iter()
+            .zip(Self::shard(residual, shard_count))
+        {
+            self.dispatch_shard(session_id, peer_id, shard);
+        }
+    }
+
+    /// Dispatch one session shard straight to `peer_id`, bypassing the generic peer-selection
+    /// queue since the session has already picked its peer. The underlying [`Attempt`] still
+    /// falls back to other peers through the ordinary retry machinery if `peer_id` itself later
+    /// fails it, same as a directly dispatched [`Self::request`] would.
+    fn dispatch_shard(&mut self, session_id: SessionId, peer_id: PeerId, hashes: BTreeSet<H256>) {
+        let request_id = self.next_request_id();
+        let request = Request::data_for_hashes(hashes.clone());
+        let pagination = Accumulator::for_request(&request).map(|acc| Pagination { peer_id, acc });
+
+        self.attempts.insert(
+            request_id,
+            Attempt {
+                request: request.clone(),
+                tried: [peer_id].into(),
+                last_error: None,
+                pagination,
+            },
+        );
+
+        let outbound_request_id = self.inner.send_request(&peer_id, request);
+        self.ongoing_requests.insert(outbound_request_id, request_id);
+
+        self.session_requests.insert(request_id, session_id);
+        self.sessions
+            .get_mut(&session_id)
+            .expect("session exists while dispatching its shards")
+            .in_flight
+            .insert(request_id, hashes);
+    }
+
+    /// Fold a finished shard's outcome into its session, then try to make further progress (see
+    /// [`Self::dispatch_session`]): success bounces back whatever the peer didn't have and
+    /// excludes that peer if it didn't have everything (it already told us so, re-asking it for
+    /// the same hashes would just spin); failure bounces back the whole shard and excludes every
+    /// peer it was tried against.
+    fn complete_shard(
+        &mut self,
+        session_id: SessionId,
+        request_id: RequestId,
+        outcome: ShardOutcome,
+    ) {
+        let Some(session) = self.sessions.get_mut(&session_id) else {
+            // Already finalized by a sibling shard that completed earlier in the same poll.
+            return;
+        };
+        let Some(shard) = session.in_flight.remove(&request_id) else {
+            return;
+        };
+
+        match outcome {
+            ShardOutcome::Succeeded { peer_id, response } => {
+                let Response::DataForHashes(items) = response else {
+                    unreachable!("sync sessions only ever dispatch DataForHashes shards")
+                };
+                let fetched: BTreeSet<H256> = items.into_keys().collect();
+                let still_missing: BTreeSet<H256> = shard.difference(&fetched).copied().collect();
+                if !still_missing.is_empty() {
+                    session.excluded_peers.insert(peer_id);
+                }
+                session.residual.extend(still_missing);
+            }
+            ShardOutcome::Failed { peers_tried } => {
+                session.excluded_peers.extend(peers_tried);
+                session.residual.extend(shard);
+            }
+        }
+
+        self.dispatch_session(session_id);
+    }
+
+    /// If `request_id` belongs to a sync session, fold `to_swarm`'s `RequestFailed` into the
+    /// session's shard bookkeeping and return `None`; otherwise return `to_swarm` unchanged for
+    /// the caller to emit directly. Shared by every place a request can be given up on: the
+    /// ordinary retry engine (`Self::retry_or_give_up`) and `Self::dispatch_pending`'s own
+    /// max-attempts check while a request is still sitting in the queue.
+    fn finish_failed_request(
+        &mut self,
+        request_id: RequestId,
+        to_swarm: ToSwarm<Event, THandlerInEvent<Self>>,
+    ) -> Option<ToSwarm<Event, THandlerInEvent<Self>>> {
+        let Some(session_id) = self.session_requests.remove(&request_id) else {
+            return Some(to_swarm);
+        };
+        let ToSwarm::GenerateEvent(Event::RequestFailed { peers_tried, .. }) = to_swarm else {
+            unreachable!(
+                "give_up/retry_or_give_up only ever produce Event::RequestFailed, so any \
+                 to_swarm reaching a tracked session_id is one of those"
+            )
+        };
+        self.complete_shard(session_id, request_id, ShardOutcome::Failed { peers_tried });
+        None
+    }
+
+    /// Split `hashes` into up to `shard_count` roughly-equal, non-empty chunks.
+    fn shard(hashes: BTreeSet<H256>, shard_count: usize) -> Vec<BTreeSet<H256>> {
+        let hashes: Vec<H256> = hashes.into_iter().collect();
+        let shard_count = shard_count.max(1).min(hashes.len().max(1));
+        let chunk_size = (hashes.len() + shard_count - 1) / shard_count;
+        hashes
+            .chunks(chunk_size.max(1))
+            .map(|chunk| chunk.iter().copied().collect())
+            .collect()
+    }
+
+    /// Give up on `request_id`, emitting its terminal [`Event::RequestFailed`].
+    fn give_up(&mut self, request_id: RequestId) -> ToSwarm<Event, THandlerInEvent<Self>> {
+        let attempt = self
+            .attempts
+            .remove(&request_id)
+            .expect("request_id still tracked while pending or ongoing");
+
+        ToSwarm::GenerateEvent(Event::RequestFailed {
+            request_id,
+            error: attempt.last_error.unwrap_or(RequestFailure::NoResponse),
+            peers_tried: attempt.tried.into_iter().collect(),
+        })
+    }
+
+    /// Record that `request_id` failed against its current peer and either queue it for retry
+    /// against a different one, or give up if every eligible peer (or `max_attempts`) has been
+    /// exhausted.
+    fn retry_or_give_up(
+        &mut self,
+        request_id: RequestId,
+        error: RequestFailure,
+    ) -> Option<ToSwarm<Event, THandlerInEvent<Self>>> {
+        let attempt = self.attempts.get_mut(&request_id)?;
+        attempt.last_error = Some(error);
+
+        if attempt.tried.len() >= self.max_attempts
+            || self
+                .connections
+                .keys()
+                .all(|peer_id| attempt.tried.contains(peer_id))
+        {
+            return Some(self.give_up(request_id));
+        }
+
+        self.pending_requests.push_back(request_id);
+        None
+    }
+
+    /// Pick the next untried, connected peer for the first dispatchable request in
+    /// `pending_requests` and dispatch it, round-robining across peers as attempts accumulate.
+    ///
+    /// Scans past (rather than stopping at) a request with no currently-eligible peer, so one
+    /// request stuck waiting on a specific peer can't head-of-line block every request queued
+    /// behind it; it's left in place and revisited on the next call.
+    fn dispatch_pending(&mut self) -> Option<ToSwarm<Event, THandlerInEvent<Self>>> {
+        for idx in 0..self.pending_requests.len() {
+            let request_id = *self.pending_requests.get(idx)?;
+            let attempt = self.attempts.get(&request_id)?;
+
+            if attempt.tried.len() >= self.max_attempts {
+                self.pending_requests.remove(idx);
+                let to_swarm = self.give_up(request_id);
+                return self.finish_failed_request(request_id, to_swarm);
+            }
+
+            let Some(peer_id) = self
+                .connections
+                .keys()
+                .find(|peer_id| !attempt.tried.contains(*peer_id) && !self.is_banned(peer_id))
+                .copied()
+            else {
+                // No untried peer connected right now for this one; leave it in the queue and
+                // move on to the next request instead of stalling on it.
+                continue;
+            };
+
+            self.pending_requests.remove(idx);
+            let is_first_attempt = attempt.tried.is_empty();
+            let kind = attempt.request.kind();
+            let request = attempt.request.clone();
+
+            let attempt = self
+                .attempts
+                .get_mut(&request_id)
+                .expect("just looked up above");
+            attempt.tried.insert(peer_id);
+            // A fresh dispatch always starts pagination over from this peer's first page,
+            // discarding whatever a previous peer may have already sent us.
+            attempt.pagination =
+                Accumulator::for_request(&attempt.request).map(|acc| Pagination { peer_id, acc });
+
+            let outbound_request_id = self.inner.send_request(&peer_id, request);
+            self.ongoing_requests.insert(outbound_request_id, request_id);
+
+            return is_first_attempt.then_some(ToSwarm::GenerateEvent(Event::RequestInitiated {
+                request_id,
+                kind,
+            }));
+        }
+
+        None
+    }
+
+    /// Merge a page of a paginated response into its attempt's [`Accumulator`] and, if
+    /// `next_cursor` is `Some`, send the next page request to the same peer. Returns the fully
+    /// reassembled [`Response`] once a non-partial (terminal) page has been folded in.
+    fn accumulate_page(
+        &mut self,
+        request_id: RequestId,
+        peer_id: PeerId,
+        response: Response,
+    ) -> Option<Response> {
+        let attempt = self
+            .attempts
+            .get_mut(&request_id)
+            .expect("request still tracked while a response for it is outstanding");
+
+        match response {
+            Response::DataForHashesPartial { items, next_cursor } => {
+                let pagination = attempt
+                    .pagination
+                    .as_mut()
+                    .expect("a DataForHashesPartial page implies an in-progress accumulator");
+                let Accumulator::DataForHashes(collected) = &mut pagination.acc else {
+                    unreachable!("accumulator kind fixed by the request kind at dispatch time")
+                };
+                collected.extend(items);
+
+                let Request::DataForHashes { hashes, budget, .. } = &attempt.request else {
+                    unreachable!("request kind fixed alongside the accumulator kind")
+                };
+                let next_request = Request::DataForHashes {
+                    hashes: hashes.clone(),
+                    after: Some(next_cursor),
+                    budget: *budget,
+                };
+                let outbound_request_id = self.inner.send_request(&peer_id, next_request);
+                self.ongoing_requests.insert(outbound_request_id, request_id);
+                None
+            }
+            Response::BlockEndProgramStatesPartial {
+                states, next_cursor, ..
+            } => {
+                let pagination = attempt.pagination.as_mut().expect(
+                    "a BlockEndProgramStatesPartial page implies an in-progress accumulator",
+                );
+                let Accumulator::BlockEndProgramStates {
+                    block_hash,
+                    states: collected,
+                } = &mut pagination.acc
+                else {
+                    unreachable!("accumulator kind fixed by the request kind at dispatch time")
+                };
+                collected.extend(states);
+
+                let Request::BlockEndProgramStates { budget, .. } = &attempt.request else {
+                    unreachable!("request kind fixed alongside the accumulator kind")
+                };
+                let next_request = Request::BlockEndProgramStates {
+                    block_hash: *block_hash,
+                    after: Some(next_cursor),
+                    budget: *budget,
+                };
+                let outbound_request_id = self.inner.send_request(&peer_id, next_request);
+                self.ongoing_requests.insert(outbound_request_id, request_id);
+                None
+            }
+            Response::DataForHashes(last) => {
+                let pagination = attempt
+                    .pagination
+                    .take()
+                    .expect("a DataForHashes response implies an in-progress accumulator");
+                let Accumulator::DataForHashes(mut collected) = pagination.acc else {
+                    unreachable!("accumulator kind fixed by the request kind at dispatch time")
+                };
+                collected.extend(last);
+                Some(Response::DataForHashes(collected))
+            }
+            Response::BlockEndProgramStates {
+                block_hash,
+                states: last,
+            } => {
+                let pagination = attempt
+                    .pagination
+                    .take()
+                    .expect("a BlockEndProgramStates response implies an in-progress accumulator");
+                let Accumulator::BlockEndProgramStates {
+                    states: mut collected,
+                    ..
+                } = pagination.acc
+                else {
+                    unreachable!("accumulator kind fixed by the request kind at dispatch time")
+                };
+                collected.extend(last);
+                Some(Response::BlockEndProgramStates {
+                    block_hash,
+                    states: collected,
+                })
+            }
+            response @ Response::ProgramCodeIds(_) => Some(response),
+        }
+    }
+
+    /// Split `items` into a page that fits `budget`, plus the key to resume from if there's more.
+    /// Always takes at least one item so a single oversized value can't stall pagination forever;
+    /// returns `None` for the cursor once every item has been taken.
+    fn paginate<K: Ord + Clone, V: Encode>(
+        items: BTreeMap<K, V>,
+        budget: Budget,
+    ) -> (BTreeMap<K, V>, Option<K>) {
+        let mut taken = BTreeMap::new();
+        let mut bytes = 0usize;
+        let mut iter = items.into_iter().peekable();
+
+        while let Some((key, value)) = iter.next() {
+            bytes += value.encoded_size();
+            taken.insert(key.clone(), value);
+
+            let over_budget = taken.len() as u32 >= budget.max_items
+                || iter.peek().is_some_and(|(_, next)| {
+                    bytes + next.encoded_size() > budget.max_bytes as usize
+                });
+            if over_budget && iter.peek().is_some() {
+                return (taken, Some(key));
+            }
+        }
+
+        (taken, None)
+    }
+
     fn read_db(&self, request: Request) -> JoinHandle<Response> {
         let db = self.db.clone();
         tokio::task::spawn_blocking(move || match request {
-            Request::BlockEndProgramStates(block_hash) => Response::BlockEndProgramStates {
+            Request::BlockEndProgramStates {
                 block_hash,
-                states: db.block_end_program_states(block_hash).unwrap_or_default(),
-            },
-            Request::DataForHashes(hashes) => Response::DataForHashes(
-                hashes
+                after,
+                budget,
+            } => {
+                let mut states: BTreeMap<ActorId, H256> =
+                    db.block_end_program_states(block_hash).unwrap_or_default();
+                if let Some(after) = after {
+                    states = states.split_off(&after);
+                    states.remove(&after);
+                }
+
+                match Self::paginate(states, budget) {
+                    (states, Some(next_cursor)) => Response::BlockEndProgramStatesPartial {
+                        block_hash,
+                        states,
+                        next_cursor,
+                    },
+                    (states, None) => Response::BlockEndProgramStates { block_hash, states },
+                }
+            }
+            Request::DataForHashes {
+                hashes,
+                after,
+                budget,
+            } => {
+                let items: BTreeMap<H256, Vec<u8>> = hashes
                     .into_iter()
+                    .filter(|hash| after.map_or(true, |after| *hash > after))
                     .filter_map(|hash| Some((hash, db.read_by_hash(hash)?)))
-                    .collect(),
-            ),
+                    .collect();
+
+                match Self::paginate(items, budget) {
+                    (items, Some(next_cursor)) => {
+                        Response::DataForHashesPartial { items, next_cursor }
+                    }
+                    (items, None) => Response::DataForHashes(items),
+                }
+            }
             Request::ProgramCodeIds(ids) => Response::ProgramCodeIds(
                 ids.into_iter()
                     .filter_map(|program_id| Some((program_id, db.program_code_id(program_id)?)))
@@ -244,50 +993,116 @@ impl Behaviour {
                         channel,
                     },
             } => {
-                self.ongoing_response = Some((channel, self.read_db(request)));
+                if self.ongoing_responses.len() >= self.max_concurrent_responses {
+                    // Over capacity: refuse the stream by dropping the channel unanswered rather
+                    // than silently evicting an older in-flight read, so the remote sees an
+                    // `InboundFailure` on our end and can retry against another peer.
+                    log::debug!(
+                        "db-sync responder at capacity ({}), refusing inbound request",
+                        self.max_concurrent_responses
+                    );
+                    drop(channel);
+                } else {
+                    self.ongoing_responses.push((channel, self.read_db(request)));
+                }
             }
+            // `protocol` is the entry out of `SUPPORTED_PROTOCOLS` multistream-select actually
+            // negotiated for this stream, as reported by the inner behaviour's codec.
             request_response::Event::Message {
                 peer,
                 message:
                     Message::Response {
                         request_id,
+                        protocol,
                         response,
                     },
             } => {
-                let (request_id, request) = self
+                let request_id = self
                     .ongoing_requests
                     .remove(&request_id)
                     .expect("unknown response");
+                let request = &self
+                    .attempts
+                    .get(&request_id)
+                    .expect("request still tracked while a response for it is outstanding")
+                    .request;
 
-                let event = match request.validate_response(&response) {
-                    Ok(()) => Event::RequestSucceed {
-                        request_id,
-                        peer_id: peer,
-                        response,
-                    },
-                    Err(error) => Event::RequestFailed { request_id, error },
-                };
-
-                return Poll::Ready(ToSwarm::GenerateEvent(event));
+                match request.validate_response(&response) {
+                    Ok(()) => {
+                        // `None` means `response` was a page and a follow-up request for the rest
+                        // of the cursor range has already been sent to `peer`; the attempt stays
+                        // outstanding until a terminal (non-partial) page comes back.
+                        if let Some(response) = self.accumulate_page(request_id, peer, response) {
+                            self.attempts.remove(&request_id);
+                            if let Some(session_id) = self.session_requests.remove(&request_id) {
+                                self.complete_shard(
+                                    session_id,
+                                    request_id,
+                                    ShardOutcome::Succeeded {
+                                        peer_id: peer,
+                                        response,
+                                    },
+                                );
+                            } else {
+                                return Poll::Ready(ToSwarm::GenerateEvent(Event::RequestSucceed {
+                                    request_id,
+                                    peer_id: peer,
+                                    protocol,
+                                    response,
+                                }));
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        self.report(peer, error);
+                        if let Some(to_swarm) = self.retry_or_give_up(request_id, error) {
+                            if let Some(to_swarm) =
+                                self.finish_failed_request(request_id, to_swarm)
+                            {
+                                return Poll::Ready(to_swarm);
+                            }
+                        }
+                    }
+                }
             }
             request_response::Event::OutboundFailure {
                 peer,
-                request_id: _,
-                error: OutboundFailure::UnsupportedProtocols,
+                request_id,
+                error,
             } => {
-                log::debug!("Request to {peer} failed because it doesn't support {STREAM_PROTOCOL} protocol. Disconnecting...");
-                return Poll::Ready(ToSwarm::CloseConnection {
-                    peer_id: peer,
-                    connection: CloseConnection::All,
-                });
+                let unsupported_protocols = matches!(error, OutboundFailure::UnsupportedProtocols);
+                if unsupported_protocols {
+                    // Reaching here means the peer didn't share a single protocol version with us
+                    // out of all of `SUPPORTED_PROTOCOLS`, not just our newest one, so there's
+                    // nothing left to fall back to with this particular peer.
+                    log::debug!("Request to {peer} failed because it doesn't support any of {SUPPORTED_PROTOCOLS:?}. Disconnecting...");
+                }
+
+                self.report(peer, RequestFailure::NoResponse);
+
+                if let Some(request_id) = self.ongoing_requests.remove(&request_id) {
+                    if let Some(to_swarm) =
+                        self.retry_or_give_up(request_id, RequestFailure::NoResponse)
+                    {
+                        if let Some(to_swarm) = self.finish_failed_request(request_id, to_swarm) {
+                            return Poll::Ready(to_swarm);
+                        }
+                    }
+                }
+
+                if unsupported_protocols {
+                    return Poll::Ready(ToSwarm::CloseConnection {
+                        peer_id: peer,
+                        connection: CloseConnection::All,
+                    });
+                }
             }
-            request_response::Event::OutboundFailure { .. } => {}
             request_response::Event::InboundFailure {
                 peer,
                 request_id: _,
                 error: InboundFailure::UnsupportedProtocols,
             } => {
-                log::debug!("Request from {peer} failed because it doesn't support {STREAM_PROTOCOL} protocol. Disconnecting...");
+                log::debug!("Request from {peer} failed because it doesn't support any of {SUPPORTED_PROTOCOLS:?}. Disconnecting...");
                 return Poll::Ready(ToSwarm::CloseConnection {
                     peer_id: peer,
                     connection: CloseConnection::All,
@@ -322,6 +1137,10 @@ impl NetworkBehaviour for Behaviour {
         local_addr: &Multiaddr,
         remote_addr: &Multiaddr,
     ) -> Result<THandler<Self>, ConnectionDenied> {
+        if self.is_banned(&peer) {
+            return Err(ConnectionDenied::new(PeerBanned(peer)));
+        }
+
         self.inner.handle_established_inbound_connection(
             connection_id,
             peer,
@@ -337,6 +1156,12 @@ impl NetworkBehaviour for Behaviour {
         addresses: &[Multiaddr],
         effective_role: Endpoint,
     ) -> Result<Vec<Multiaddr>, ConnectionDenied> {
+        if let Some(peer) = maybe_peer {
+            if self.is_banned(&peer) {
+                return Err(ConnectionDenied::new(PeerBanned(peer)));
+            }
+        }
+
         self.inner.handle_pending_outbound_connection(
             connection_id,
             maybe_peer,
@@ -398,27 +1223,30 @@ impl NetworkBehaviour for Behaviour {
         &mut self,
         cx: &mut Context<'_>,
     ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
-        // TODO: way to choose peer
-        if let Some(peer_id) = self.connections.keys().next() {
-            if let Some((request_id, request)) = self.user_requests.pop_back() {
-                let request_kind = request.kind();
-                let outbound_request_id = self.inner.send_request(peer_id, request.clone());
-                self.ongoing_requests
-                    .insert(outbound_request_id, (request_id, request));
-
-                return Poll::Ready(ToSwarm::GenerateEvent(Event::RequestInitiated {
-                    request_id,
-                    kind: request_kind,
-                }));
-            }
+        if let Some(peer_id) = self.pending_closes.pop_front() {
+            return Poll::Ready(ToSwarm::CloseConnection {
+                peer_id,
+                connection: CloseConnection::All,
+            });
+        }
+
+        if let Some(event) = self.pending_session_events.pop_front() {
+            return Poll::Ready(ToSwarm::GenerateEvent(event));
         }
 
-        if let Some((channel, mut db_reader)) = self.ongoing_response.take() {
-            if let Poll::Ready(data) = db_reader.poll_unpin(cx) {
+        if let Some(to_swarm) = self.dispatch_pending() {
+            return Poll::Ready(to_swarm);
+        }
+
+        let mut i = 0;
+        while i < self.ongoing_responses.len() {
+            if let Poll::Ready(data) = self.ongoing_responses[i].1.poll_unpin(cx) {
+                let (channel, _) = self.ongoing_responses.swap_remove(i);
                 let resp = data.expect("database panicked");
                 let _res = self.inner.send_response(channel, resp);
+                // `swap_remove` moved the last element into slot `i`; don't advance past it.
             } else {
-                self.ongoing_response = Some((channel, db_reader));
+                i += 1;
             }
         }
 
@@ -445,7 +1273,11 @@ mod tests {
 
     async fn new_swarm() -> (Swarm<Behaviour>, Database) {
         let db = Database::from_one(&MemDb::default());
-        let behaviour = Behaviour::new(request_response::Config::default(), db.clone());
+        let behaviour = Behaviour::new(
+            request_response::Config::default(),
+            Config::default(),
+            db.clone(),
+        );
         let mut swarm = Swarm::new_ephemeral(move |_keypair| behaviour);
         swarm.listen().with_memory_addr_external().await;
         (swarm, db)
@@ -457,7 +1289,7 @@ mod tests {
         let hash2 = ethexe_db::hash(b"2");
         let hash3 = ethexe_db::hash(b"3");
 
-        let request = Request::DataForHashes([hash1, hash2].into());
+        let request = Request::data_for_hashes([hash1, hash2].into());
         let response = Response::DataForHashes(
             [
                 (hash1, b"1".to_vec()),
@@ -490,7 +1322,7 @@ mod tests {
     fn validate_data_hash_mismatch() {
         let hash1 = ethexe_db::hash(b"1");
 
-        let request = Request::DataForHashes([hash1].into());
+        let request = Request::data_for_hashes([hash1].into());
         let response = Response::DataForHashes([(hash1, b"2".to_vec())].into());
         assert_eq!(
             request.validate_response(&response),
@@ -498,6 +1330,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dispatch_pending_skips_past_a_head_of_line_blocked_request() {
+        let db = Database::from_one(&MemDb::default());
+        let mut behaviour = Behaviour::new(
+            request_response::Config::default(),
+            Config {
+                max_attempts: 2,
+                ..Config::default()
+            },
+            db,
+        );
+
+        let peer_a = PeerId::random();
+        behaviour.connections.insert(peer_a, HashSet::new());
+
+        // `blocked` has already been tried against the only connected peer, but hasn't hit
+        // `max_attempts` yet, so it sits in the queue waiting for a different peer to connect.
+        let blocked = behaviour.request(Request::data_for_hashes([].into()));
+        behaviour
+            .attempts
+            .get_mut(&blocked)
+            .unwrap()
+            .tried
+            .insert(peer_a);
+
+        // `fresh` is queued behind it and hasn't tried any peer yet.
+        let fresh = behaviour.request(Request::data_for_hashes([].into()));
+
+        let to_swarm = behaviour
+            .dispatch_pending()
+            .expect("fresh is dispatchable even though blocked is stuck at the front");
+        let ToSwarm::GenerateEvent(event) = to_swarm else {
+            unreachable!("dispatch_pending only ever produces Event::RequestInitiated here")
+        };
+        assert_eq!(
+            event,
+            Event::RequestInitiated {
+                request_id: fresh,
+                kind: RequestKind::DataForHashes,
+            }
+        );
+
+        assert_eq!(behaviour.pending_requests, VecDeque::from([blocked]));
+    }
+
+    #[test]
+    fn bans_peer_once_reputation_crosses_threshold() {
+        let db = Database::from_one(&MemDb::default());
+        let mut behaviour = Behaviour::new(
+            request_response::Config::default(),
+            Config {
+                ban_threshold: 40,
+                ..Config::default()
+            },
+            db,
+        );
+        let peer_id = PeerId::random();
+
+        behaviour.report(peer_id, RequestFailure::DataHashMismatch);
+        assert!(!behaviour.is_banned(&peer_id));
+
+        behaviour.report(peer_id, RequestFailure::DataHashMismatch);
+        assert!(behaviour.is_banned(&peer_id));
+
+        behaviour.unban_peer(&peer_id);
+        assert!(!behaviour.is_banned(&peer_id));
+    }
+
     #[tokio::test]
     async fn smoke() {
         init_logger();
@@ -514,7 +1414,7 @@ mod tests {
 
         alice
             .behaviour_mut()
-            .request(Request::DataForHashes([hello_hash, world_hash].into()));
+            .request(Request::data_for_hashes([hello_hash, world_hash].into()));
 
         let event = alice.next_behaviour_event().await;
         let request_id = if let Event::RequestInitiated {
@@ -533,6 +1433,7 @@ mod tests {
             Event::RequestSucceed {
                 peer_id: bob_id,
                 request_id,
+                protocol: SUPPORTED_PROTOCOLS[0].clone(),
                 response: Response::DataForHashes(
                     [
                         (hello_hash, b"hello".to_vec()),
@@ -544,6 +1445,272 @@ mod tests {
         )
     }
 
+    #[tokio::test]
+    async fn paginates_large_responses_across_multiple_pages() {
+        init_logger();
+
+        // A budget of one item per page forces bob to answer with three
+        // `DataForHashesPartial` pages in a row; alice should reassemble them into a single
+        // `RequestSucceed` carrying all three items, none of it visible as separate events.
+        let (mut alice, _alice_db) = new_swarm().await;
+        let (mut bob, bob_db) = new_swarm().await;
+        let bob_id = *bob.local_peer_id();
+
+        let hello_hash = bob_db.write(b"hello");
+        let world_hash = bob_db.write(b"world");
+        let third_hash = bob_db.write(b"gear");
+
+        alice.connect(&mut bob).await;
+        tokio::spawn(bob.loop_on_next());
+
+        alice.behaviour_mut().request(Request::DataForHashes {
+            hashes: [hello_hash, world_hash, third_hash].into(),
+            after: None,
+            budget: Budget {
+                max_items: 1,
+                max_bytes: u32::MAX,
+            },
+        });
+
+        let event = alice.next_behaviour_event().await;
+        let request_id = if let Event::RequestInitiated {
+            request_id: outbound_request_id,
+            kind: RequestKind::DataForHashes,
+        } = event
+        {
+            outbound_request_id
+        } else {
+            unreachable!()
+        };
+
+        let event = alice.next_behaviour_event().await;
+        assert_eq!(
+            event,
+            Event::RequestSucceed {
+                peer_id: bob_id,
+                request_id,
+                protocol: SUPPORTED_PROTOCOLS[0].clone(),
+                response: Response::DataForHashes(
+                    [
+                        (hello_hash, b"hello".to_vec()),
+                        (world_hash, b"world".to_vec()),
+                        (third_hash, b"gear".to_vec()),
+                    ]
+                    .into()
+                )
+            }
+        )
+    }
+
+    #[tokio::test]
+    async fn sync_data_fans_out_across_peers_and_completes() {
+        init_logger();
+
+        // Both bob and carol hold every hash, so sync_data's shard-to-peer assignment (which is
+        // unspecified) can't affect the outcome: whichever peer a shard lands on, it can answer.
+        let (mut alice, _alice_db) = new_swarm().await;
+        let (mut bob, bob_db) = new_swarm().await;
+        let (mut carol, carol_db) = new_swarm().await;
+
+        let hello_hash = bob_db.write(b"hello");
+        let world_hash = bob_db.write(b"world");
+        let _ = carol_db.write(b"hello");
+        let _ = carol_db.write(b"world");
+
+        alice.connect(&mut bob).await;
+        alice.connect(&mut carol).await;
+        tokio::spawn(bob.loop_on_next());
+        tokio::spawn(carol.loop_on_next());
+
+        let session_id = alice
+            .behaviour_mut()
+            .sync_data([hello_hash, world_hash].into());
+
+        loop {
+            if let Event::SyncDataCompleted {
+                session_id: completed_session_id,
+                missing,
+            } = alice.next_behaviour_event().await
+            {
+                assert_eq!(completed_session_id, session_id);
+                assert_eq!(missing, BTreeSet::new());
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_data_reports_hashes_no_connected_peer_could_provide() {
+        init_logger();
+
+        // `bob` doesn't have `missing_hash`, so the session must exhaust him and report it back
+        // as still missing rather than hanging forever.
+        let (mut alice, _alice_db) = new_swarm().await;
+        let (mut bob, bob_db) = new_swarm().await;
+
+        let hello_hash = bob_db.write(b"hello");
+        let missing_hash = ethexe_db::hash(b"never written");
+
+        alice.connect(&mut bob).await;
+        tokio::spawn(bob.loop_on_next());
+
+        let session_id = alice
+            .behaviour_mut()
+            .sync_data([hello_hash, missing_hash].into());
+
+        loop {
+            if let Event::SyncDataCompleted {
+                session_id: completed_session_id,
+                missing,
+            } = alice.next_behaviour_event().await
+            {
+                assert_eq!(completed_session_id, session_id);
+                assert_eq!(missing, [missing_hash].into());
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_against_another_peer_after_invalid_response() {
+        init_logger();
+
+        // `bad` always answers with the wrong response kind; `good` is a real db-backed peer.
+        // Whichever one alice tries first, she must end up with `good`'s valid answer rather than
+        // giving up after `bad`'s bogus one.
+        let (mut alice, _alice_db) = new_swarm().await;
+        let (mut good, good_db) = new_swarm().await;
+        let good_id = *good.local_peer_id();
+        let mut bad = Swarm::new_ephemeral(move |_keypair| {
+            InnerBehaviour::new(
+                SUPPORTED_PROTOCOLS
+                    .iter()
+                    .cloned()
+                    .map(|protocol| (protocol, ProtocolSupport::Full)),
+                request_response::Config::default(),
+            )
+        });
+
+        let hello_hash = good_db.write(b"hello");
+
+        alice.connect(&mut bad).await;
+        alice.connect(&mut good).await;
+        tokio::spawn(good.loop_on_next());
+
+        alice
+            .behaviour_mut()
+            .request(Request::data_for_hashes([hello_hash].into()));
+
+        let event = alice.next_behaviour_event().await;
+        let request_id = if let Event::RequestInitiated {
+            request_id,
+            kind: RequestKind::DataForHashes,
+        } = event
+        {
+            request_id
+        } else {
+            unreachable!()
+        };
+
+        loop {
+            tokio::select! {
+                event = bad.next_behaviour_event() => {
+                    if let request_response::Event::Message {
+                        message: Message::Request { channel, .. },
+                        ..
+                    } = event
+                    {
+                        let _res = bad
+                            .behaviour_mut()
+                            .send_response(channel, Response::ProgramCodeIds([].into()));
+                    }
+                }
+                event = alice.next_behaviour_event() => {
+                    assert_eq!(
+                        event,
+                        Event::RequestSucceed {
+                            peer_id: good_id,
+                            request_id,
+                            protocol: SUPPORTED_PROTOCOLS[0].clone(),
+                            response: Response::DataForHashes(
+                                [(hello_hash, b"hello".to_vec())].into()
+                            ),
+                        }
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_legacy_protocol() {
+        init_logger();
+
+        // Bob only ever advertised the pre-versioning protocol, as a peer running an old release
+        // would. Alice should still negotiate it via `SUPPORTED_PROTOCOLS` instead of disconnecting.
+        let legacy_protocol = SUPPORTED_PROTOCOLS.last().unwrap().clone();
+
+        let (mut alice, _alice_db) = new_swarm().await;
+        let mut bob = Swarm::new_ephemeral(move |_keypair| {
+            InnerBehaviour::new(
+                [(legacy_protocol.clone(), ProtocolSupport::Full)],
+                request_response::Config::default(),
+            )
+        });
+        bob.connect(&mut alice).await;
+
+        alice
+            .behaviour_mut()
+            .request(Request::data_for_hashes([].into()));
+
+        let event = alice.next_behaviour_event().await;
+        let request_id = if let Event::RequestInitiated {
+            request_id,
+            kind: RequestKind::DataForHashes,
+        } = event
+        {
+            request_id
+        } else {
+            unreachable!()
+        };
+
+        loop {
+            tokio::select! {
+                event = bob.next_behaviour_event() => {
+                    match event {
+                        request_response::Event::Message {
+                            message:
+                                Message::Request {
+                                    channel, request, ..
+                                },
+                            ..
+                        } => {
+                            assert_eq!(request, Request::data_for_hashes([].into()));
+                            let _res = bob
+                                .behaviour_mut()
+                                .send_response(channel, Response::DataForHashes([].into()));
+                        }
+                        request_response::Event::ResponseSent { .. } => continue,
+                        e => unreachable!("unexpected event: {:?}", e),
+                    }
+                }
+                event = alice.next_behaviour_event() => {
+                    assert_eq!(
+                        event,
+                        Event::RequestSucceed {
+                            peer_id: *bob.local_peer_id(),
+                            request_id,
+                            protocol: SUPPORTED_PROTOCOLS.last().unwrap().clone(),
+                            response: Response::DataForHashes([].into()),
+                        }
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
     #[tokio::test]
     async fn request_response_type_mismatch() {
         init_logger();
@@ -551,7 +1718,10 @@ mod tests {
         let (mut alice, _alice_db) = new_swarm().await;
         let mut bob = Swarm::new_ephemeral(move |_keypair| {
             InnerBehaviour::new(
-                [(STREAM_PROTOCOL, ProtocolSupport::Full)],
+                SUPPORTED_PROTOCOLS
+                    .iter()
+                    .cloned()
+                    .map(|protocol| (protocol, ProtocolSupport::Full)),
                 request_response::Config::default(),
             )
         });
@@ -559,7 +1729,7 @@ mod tests {
 
         alice
             .behaviour_mut()
-            .request(Request::DataForHashes([].into()));
+            .request(Request::data_for_hashes([].into()));
 
         let event = alice.next_behaviour_event().await;
         let request_id = if let Event::RequestInitiated {
@@ -583,7 +1753,7 @@ mod tests {
                                 },
                             ..
                         } => {
-                            assert_eq!(request, Request::DataForHashes([].into()));
+                            assert_eq!(request, Request::data_for_hashes([].into()));
                             let _res = bob
                                 .behaviour_mut()
                                 .send_response(channel, Response::ProgramCodeIds([].into()));
@@ -597,7 +1767,8 @@ mod tests {
                         event,
                         Event::RequestFailed {
                             request_id,
-                            error: RequestFailure::TypeMismatch
+                            error: RequestFailure::TypeMismatch,
+                            peers_tried: [*bob.local_peer_id()].into(),
                         }
                     );
                     break;
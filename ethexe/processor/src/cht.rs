@@ -0,0 +1,274 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Canonical-hash-trie checkpoints over per-block aggregate program-state roots.
+//!
+//! Blocks are partitioned into fixed-size intervals of [`INTERVAL_LEN`] blocks. The leaf for a
+//! block is its aggregate root: a hash over the full `BTreeMap<ProgramId, H256>` of that block's
+//! end program states. Once an interval closes only its trie root is kept long-term; the raw
+//! per-block maps needed to build a full [`StateProof`] are retained for the archival window and
+//! dropped afterwards, so storage stays O(1) per interval while recent blocks remain provable in
+//! O(log n).
+
+use gear_core::ids::ProgramId;
+use gprimitives::H256;
+use parity_scale_codec::{Decode, Encode};
+use sp_core::blake2_256;
+use std::collections::BTreeMap;
+
+/// Number of blocks aggregated under a single CHT interval root.
+pub const INTERVAL_LEN: u32 = 256;
+
+/// Proof that `program_id`'s state hash was `state_hash` at `block_number`, anchored at
+/// `interval_root`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct StateProof {
+    pub interval_root: H256,
+    /// Index of the block's aggregate-root leaf within its interval.
+    pub block_index: u32,
+    /// Sibling hashes from the block's leaf up to `interval_root`.
+    pub block_path: Vec<H256>,
+    /// Index of `program_id`'s leaf within the block's aggregate-root map.
+    pub program_index: u32,
+    /// Sibling hashes from the program's leaf up to the block's aggregate root.
+    pub program_path: Vec<H256>,
+}
+
+fn hash_leaf(data: &[u8]) -> H256 {
+    H256(blake2_256(data))
+}
+
+fn hash_pair(left: H256, right: H256) -> H256 {
+    H256(blake2_256(&(left, right).encode()))
+}
+
+/// Builds a binary Merkle tree over `leaves`, duplicating the last node of a level when it has an
+/// odd length, and returns `(root, path_to(index))`.
+fn merkle_root_and_path(leaves: &[H256], mut index: usize) -> (H256, Vec<H256>) {
+    let mut level = leaves.to_vec();
+    let mut path = Vec::new();
+    while level.len() > 1 {
+        if level.len() % 2 == 1 {
+            level.push(*level.last().expect("non-empty"));
+        }
+        path.push(level[index ^ 1]);
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(pair[0], pair[1]))
+            .collect();
+        index /= 2;
+    }
+    (level[0], path)
+}
+
+fn merkle_root(leaves: &[H256]) -> H256 {
+    merkle_root_and_path(leaves, 0).0
+}
+
+/// In-memory canonical-hash-trie index. Owned by [`crate::Processor`] and fed one block's end
+/// program states at a time via [`ChtIndex::record_block`].
+#[derive(Default)]
+pub struct ChtIndex {
+    /// Completed interval roots, keyed by interval index (`block_number / INTERVAL_LEN`).
+    roots: BTreeMap<u32, H256>,
+    /// Aggregate roots for blocks in the archival window (the still-open interval and the most
+    /// recently closed one). Dropped together with `archive`.
+    leaves: BTreeMap<u32, H256>,
+    /// Raw end-state maps for blocks in the archival window, used to build [`StateProof`]s.
+    /// An interval's maps are dropped once the interval after it closes.
+    archive: BTreeMap<u32, BTreeMap<ProgramId, H256>>,
+}
+
+impl ChtIndex {
+    /// Rebuilds a [`ChtIndex`] from previously closed interval roots (e.g. reloaded from
+    /// [`crate::cht_storage::ChtStorage`] on startup). The per-block leaves and archive needed to
+    /// prove blocks *within* those intervals aren't part of `roots` and so aren't recovered by
+    /// this — only [`Self::interval_root`] works for them until the index is rebuilt from scratch.
+    pub fn with_roots(roots: BTreeMap<u32, H256>) -> Self {
+        Self {
+            roots,
+            ..Self::default()
+        }
+    }
+
+    /// Aggregate root committed to by a block's full end-state map: the Merkle root over its
+    /// `(program_id, state_hash)` leaves, or zero for an empty map.
+    pub fn aggregate_root(states: &BTreeMap<ProgramId, H256>) -> H256 {
+        let leaves: Vec<H256> = states
+            .iter()
+            .map(|(id, state)| hash_leaf(&(id, state).encode()))
+            .collect();
+        if leaves.is_empty() {
+            return H256::zero();
+        }
+        merkle_root(&leaves)
+    }
+
+    /// Records `block_number`'s end program states, closing out and caching the interval root
+    /// once the interval is complete. Returns the newly-closed `(interval, root)` pair so a
+    /// caller can write it through to persistent storage.
+    pub fn record_block(
+        &mut self,
+        block_number: u32,
+        states: &BTreeMap<ProgramId, H256>,
+    ) -> Option<(u32, H256)> {
+        let aggregate_root = Self::aggregate_root(states);
+        self.leaves.insert(block_number, aggregate_root);
+        self.archive.insert(block_number, states.clone());
+
+        let interval = block_number / INTERVAL_LEN;
+        let interval_start = interval * INTERVAL_LEN;
+        if block_number + 1 == interval_start + INTERVAL_LEN {
+            let leaves: Vec<H256> = (interval_start..interval_start + INTERVAL_LEN)
+                .map(|n| self.leaves.get(&n).copied().unwrap_or_default())
+                .collect();
+            let root = merkle_root(&leaves);
+            self.roots.insert(interval, root);
+            self.archive.retain(|n, _| n / INTERVAL_LEN >= interval);
+            self.leaves.retain(|n, _| n / INTERVAL_LEN >= interval);
+            Some((interval, root))
+        } else {
+            None
+        }
+    }
+
+    /// The interval root covering `block_number`, if its interval has closed.
+    pub fn interval_root(&self, block_number: u32) -> Option<H256> {
+        self.roots.get(&(block_number / INTERVAL_LEN)).copied()
+    }
+
+    /// Builds a [`StateProof`] for `program_id`'s state at `block_number`. Returns `None` if the
+    /// interval hasn't closed yet, or the raw map has already fallen below the archival window.
+    pub fn prove(&self, program_id: ProgramId, block_number: u32) -> Option<StateProof> {
+        let interval = block_number / INTERVAL_LEN;
+        let interval_root = *self.roots.get(&interval)?;
+        let interval_start = interval * INTERVAL_LEN;
+        let block_index = block_number - interval_start;
+
+        let interval_leaves: Vec<H256> = (interval_start..interval_start + INTERVAL_LEN)
+            .map(|n| self.leaves.get(&n).copied().unwrap_or_default())
+            .collect();
+        let (_, block_path) = merkle_root_and_path(&interval_leaves, block_index as usize);
+
+        let states = self.archive.get(&block_number)?;
+        let program_index = states.keys().position(|candidate| *candidate == program_id)?;
+        let program_leaves: Vec<H256> = states
+            .iter()
+            .map(|(id, state)| hash_leaf(&(id, state).encode()))
+            .collect();
+        let (_, program_path) = merkle_root_and_path(&program_leaves, program_index);
+
+        Some(StateProof {
+            interval_root,
+            block_index,
+            block_path,
+            program_index: program_index as u32,
+            program_path,
+        })
+    }
+}
+
+/// Stateless verification of a [`StateProof`] produced by [`ChtIndex::prove`] — doesn't need
+/// access to any historical per-block map.
+pub fn verify_state_proof(
+    program_id: ProgramId,
+    state_hash: H256,
+    proof: &StateProof,
+) -> bool {
+    let mut hash = hash_leaf(&(program_id, state_hash).encode());
+    let mut index = proof.program_index as usize;
+    for sibling in &proof.program_path {
+        hash = walk_up(hash, *sibling, index);
+        index /= 2;
+    }
+    let aggregate_root = hash;
+
+    let mut hash = aggregate_root;
+    let mut index = proof.block_index as usize;
+    for sibling in &proof.block_path {
+        hash = walk_up(hash, *sibling, index);
+        index /= 2;
+    }
+
+    hash == proof.interval_root
+}
+
+fn walk_up(hash: H256, sibling: H256, index: usize) -> H256 {
+    if index % 2 == 0 {
+        hash_pair(hash, sibling)
+    } else {
+        hash_pair(sibling, hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prove_and_verify_within_archival_window() {
+        let mut index = ChtIndex::default();
+        let program_id = ProgramId::from([1u8; 32]);
+        let state_hash = H256::random();
+
+        for block_number in 0..INTERVAL_LEN {
+            let states: BTreeMap<ProgramId, H256> = if block_number == 10 {
+                [(program_id, state_hash)].into()
+            } else {
+                [(ProgramId::from([2u8; 32]), H256::random())].into()
+            };
+            index.record_block(block_number, &states);
+        }
+
+        let proof = index.prove(program_id, 10).expect("interval closed");
+        assert!(verify_state_proof(program_id, state_hash, &proof));
+        assert!(!verify_state_proof(program_id, H256::random(), &proof));
+    }
+
+    #[test]
+    fn test_prove_returns_none_before_interval_closes() {
+        let mut index = ChtIndex::default();
+        let program_id = ProgramId::from([1u8; 32]);
+        index.record_block(0, &[(program_id, H256::random())].into());
+
+        assert!(index.prove(program_id, 0).is_none());
+    }
+
+    #[test]
+    fn test_record_block_returns_closed_interval_only_on_last_block() {
+        let mut index = ChtIndex::default();
+        let states: BTreeMap<ProgramId, H256> =
+            [(ProgramId::from([1u8; 32]), H256::random())].into();
+
+        for block_number in 0..INTERVAL_LEN - 1 {
+            assert_eq!(index.record_block(block_number, &states), None);
+        }
+
+        let closed = index.record_block(INTERVAL_LEN - 1, &states);
+        assert_eq!(closed.map(|(interval, _)| interval), Some(0));
+    }
+
+    #[test]
+    fn test_with_roots_restores_interval_root_lookup() {
+        let root = H256::random();
+        let index = ChtIndex::with_roots([(0, root)].into());
+
+        assert_eq!(index.interval_root(10), Some(root));
+        assert_eq!(index.interval_root(INTERVAL_LEN), None);
+    }
+}
@@ -0,0 +1,91 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Write-through persistence for closed [`ChtIndex`](crate::cht::ChtIndex) interval roots.
+//!
+//! Without this, a node restart loses every closed interval root, forcing a full replay of every
+//! block ever processed before [`crate::Processor::prove_state_at`] works again. [`ChtStorage`] is
+//! a small key-value boundary rather than a direct `ethexe-db` dependency: the real `ethexe-db`
+//! crate isn't part of this workspace slice, so [`crate::Processor`] is wired against this trait
+//! instead, with [`InMemoryChtStorage`] standing in until a genuine `ethexe-db`-backed
+//! implementation can be plugged in.
+
+use gprimitives::H256;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+/// A minimal write-through key-value boundary for closed CHT interval roots, standing in for an
+/// `ethexe-db` backend.
+pub trait ChtStorage: Send + Sync {
+    /// Persists `root` as the closed root of `interval`.
+    fn put_root(&self, interval: u32, root: H256);
+
+    /// Loads every closed interval root persisted so far, keyed by interval index.
+    fn roots(&self) -> BTreeMap<u32, H256>;
+}
+
+/// In-memory [`ChtStorage`], used until a real `ethexe-db`-backed implementation exists. Naturally
+/// loses all state across a restart, so it doesn't actually provide crash recovery by itself — it
+/// only exercises the write-through/reload code paths.
+#[derive(Default)]
+pub struct InMemoryChtStorage {
+    roots: Mutex<BTreeMap<u32, H256>>,
+}
+
+impl ChtStorage for InMemoryChtStorage {
+    fn put_root(&self, interval: u32, root: H256) {
+        self.roots.lock().unwrap().insert(interval, root);
+    }
+
+    fn roots(&self) -> BTreeMap<u32, H256> {
+        self.roots.lock().unwrap().clone()
+    }
+}
+
+/// Shared handle to a [`ChtStorage`] implementation.
+pub type SharedChtStorage = Arc<dyn ChtStorage>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_reload_roundtrip() {
+        let storage = InMemoryChtStorage::default();
+        assert_eq!(storage.roots(), BTreeMap::new());
+
+        storage.put_root(0, H256::from([1; 32]));
+        storage.put_root(1, H256::from([2; 32]));
+
+        assert_eq!(
+            storage.roots(),
+            [(0, H256::from([1; 32])), (1, H256::from([2; 32]))].into()
+        );
+    }
+
+    #[test]
+    fn test_put_overwrites_existing_interval() {
+        let storage = InMemoryChtStorage::default();
+        storage.put_root(0, H256::from([1; 32]));
+        storage.put_root(0, H256::from([2; 32]));
+
+        assert_eq!(storage.roots(), [(0, H256::from([2; 32]))].into());
+    }
+}
@@ -0,0 +1,160 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Reference-counted pruning for the content-addressed [`Storage`] graph.
+//!
+//! Every `write_*` on [`Storage`] creates an immutable node keyed by `H256`, and nothing reclaims
+//! superseded states, old queues, or orphaned payloads on its own. [`RefCountedGc`] tracks, per
+//! hash, how many still-pinned chain heads reach it; once a head is unpinned and a hash's count
+//! drops to zero it's reported as reclaimable by [`RefCountedGc::sweep`].
+
+use ethexe_runtime_common::state::{MaybeHash, ProgramState, Storage};
+use gprimitives::H256;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// How much history [`RefCountedGc`] keeps pinned.
+#[derive(Debug, Clone, Copy)]
+pub enum PruningMode {
+    /// Never unpin a head: every state ever written stays reachable.
+    FullArchive,
+    /// Only the last `window` pinned chain heads are kept; older ones are unpinned as new heads
+    /// are pinned.
+    Windowed { window: usize },
+}
+
+/// Reference-counted GC over the hashes reachable from a set of pinned chain heads.
+pub struct RefCountedGc {
+    mode: PruningMode,
+    /// Reference count per reachable hash, summed across every currently-pinned head.
+    refs: BTreeMap<H256, u32>,
+    /// Reachable hash set per pinned head, so unpinning can decrement exactly what that head
+    /// contributed.
+    pinned: BTreeMap<H256, BTreeSet<H256>>,
+    /// Pin order, oldest first, so [`PruningMode::Windowed`] knows what to evict.
+    order: VecDeque<H256>,
+}
+
+impl RefCountedGc {
+    pub fn new(mode: PruningMode) -> Self {
+        Self {
+            mode,
+            refs: BTreeMap::new(),
+            pinned: BTreeMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Pins `chain_head`'s reachable state graph, then — under [`PruningMode::Windowed`] — unpins
+    /// the oldest head if doing so pushed the pinned set past the configured window. Returns the
+    /// hashes that became unreachable as a result, ready for the caller to reclaim from storage.
+    pub fn pin_head(
+        &mut self,
+        storage: &impl Storage,
+        chain_head: H256,
+        end_states: impl IntoIterator<Item = H256>,
+    ) -> Vec<H256> {
+        let mut reachable = BTreeSet::new();
+        for state_hash in end_states {
+            collect_reachable(storage, state_hash, &mut reachable);
+        }
+        for &hash in &reachable {
+            *self.refs.entry(hash).or_insert(0) += 1;
+        }
+        self.pinned.insert(chain_head, reachable);
+        self.order.push_back(chain_head);
+
+        let PruningMode::Windowed { window } = self.mode else {
+            return Vec::new();
+        };
+        let mut reclaimed = Vec::new();
+        while self.order.len() > window {
+            let oldest = self.order.pop_front().expect("checked len above");
+            reclaimed.extend(self.unpin_head(oldest));
+        }
+        reclaimed
+    }
+
+    /// Decrements the reference count of every hash `chain_head` contributed, returning the ones
+    /// that reached zero.
+    pub fn unpin_head(&mut self, chain_head: H256) -> Vec<H256> {
+        let Some(reachable) = self.pinned.remove(&chain_head) else {
+            return Vec::new();
+        };
+
+        let mut reclaimed = Vec::new();
+        for hash in reachable {
+            match self.refs.get_mut(&hash) {
+                Some(count) if *count > 1 => *count -= 1,
+                Some(_) => {
+                    self.refs.remove(&hash);
+                    reclaimed.push(hash);
+                }
+                None => {}
+            }
+        }
+        reclaimed
+    }
+
+    /// Whether `hash` is still reachable from some pinned head.
+    pub fn is_pinned(&self, hash: H256) -> bool {
+        self.refs.contains_key(&hash)
+    }
+}
+
+/// Transitively collects every CAS hash reachable from a program state root: its queue, memory
+/// pages, allocations, gas-reservation map and payloads.
+fn collect_reachable(storage: &impl Storage, state_hash: H256, out: &mut BTreeSet<H256>) {
+    if state_hash.is_zero() || !out.insert(state_hash) {
+        return;
+    }
+    let Some(ProgramState {
+        queue_hash,
+        allocations_hash,
+        pages_hash,
+        gas_reservation_map_hash,
+        ..
+    }) = storage.read_state(state_hash)
+    else {
+        return;
+    };
+
+    if let MaybeHash::Hash(hash_and_len) = queue_hash {
+        if out.insert(hash_and_len.hash) {
+            if let Some(queue) = storage.read_queue(hash_and_len.hash) {
+                for dispatch in queue {
+                    if let MaybeHash::Hash(hash_and_len) = dispatch.payload_hash {
+                        out.insert(hash_and_len.hash);
+                    }
+                }
+            }
+        }
+    }
+    if let MaybeHash::Hash(hash_and_len) = allocations_hash {
+        out.insert(hash_and_len.hash);
+    }
+    if let MaybeHash::Hash(hash_and_len) = pages_hash {
+        if out.insert(hash_and_len.hash) {
+            if let Some(pages) = storage.read_pages(hash_and_len.hash) {
+                out.extend(pages.into_values());
+            }
+        }
+    }
+    if let MaybeHash::Hash(hash_and_len) = gas_reservation_map_hash {
+        out.insert(hash_and_len.hash);
+    }
+}
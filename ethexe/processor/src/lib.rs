@@ -35,14 +35,30 @@ use gear_core::{
 use gprimitives::{CodeId, H256};
 use host::InstanceCreator;
 use parity_scale_codec::{Decode, Encode};
-use std::collections::{BTreeMap, VecDeque};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
 
+pub mod cht;
+pub mod cht_storage;
+pub mod gc;
 pub mod host;
+mod notify;
+pub mod pool;
+pub mod remote_storage;
 mod run;
 
 #[cfg(test)]
 mod tests;
 
+pub use cht::{verify_state_proof, StateProof};
+pub use cht_storage::{ChtStorage, InMemoryChtStorage, SharedChtStorage};
+pub use gc::PruningMode;
+pub use notify::{OutcomeSubscription, TransitionSubscription};
+pub use pool::{PoolConfig, ResetStrategy};
+pub use remote_storage::{NodeRequest, NodeResponse, PeerClient, RemoteStorage};
+
 pub struct UserMessage {
     id: MessageId,
     kind: DispatchKind,
@@ -54,6 +70,23 @@ pub struct UserMessage {
 pub struct Processor {
     db: Database,
     creator: InstanceCreator,
+    /// Canonical-hash-trie checkpoints over processed blocks' aggregate program-state roots.
+    cht: cht::ChtIndex,
+    /// Sequence number each processed block was recorded under in `cht`, since the CHT is keyed
+    /// by processing order rather than the chain's own block numbering.
+    cht_sequence: BTreeMap<H256, u32>,
+    /// Write-through persistence for `cht`'s closed interval roots, so they survive a restart
+    /// instead of forcing a full block replay before [`Self::prove_state_at`] works again. See
+    /// [`cht_storage`] for why this is a stand-in trait rather than a direct `ethexe-db` handle.
+    cht_storage: SharedChtStorage,
+    /// Reference-counted GC over the pinned chain heads' reachable state graph.
+    gc: gc::RefCountedGc,
+    /// Fans committed outcomes out to [`Self::subscribe_outcomes`]/[`Self::subscribe_transitions`]
+    /// subscribers.
+    notify: notify::NotificationHub,
+    /// Sizing/reset config for a warm-instance pool keyed by `(runtime version, code id)`. See
+    /// [`crate::pool`] for why this isn't hooked up to real pooled instances yet.
+    pool_config: PoolConfig,
 }
 
 // TODO (breathx): rename outcomes accordingly to events.
@@ -74,7 +107,56 @@ pub enum LocalOutcome {
 impl Processor {
     pub fn new(db: Database) -> Result<Self> {
         let creator = InstanceCreator::new(db.clone(), host::runtime())?;
-        Ok(Self { db, creator })
+        let cht_storage: SharedChtStorage = Arc::new(InMemoryChtStorage::default());
+        Ok(Self {
+            db,
+            creator,
+            cht: cht::ChtIndex::with_roots(cht_storage.roots()),
+            cht_sequence: BTreeMap::new(),
+            cht_storage,
+            gc: gc::RefCountedGc::new(PruningMode::FullArchive),
+            notify: notify::NotificationHub::default(),
+            pool_config: PoolConfig::default(),
+        })
+    }
+
+    /// Configures the warm-instance pool's size and reset strategy for future calls into
+    /// [`Self::run_on_host`].
+    pub fn configure_pool(&mut self, config: PoolConfig) {
+        self.pool_config = config;
+    }
+
+    /// Subscribes to every [`LocalOutcome`] as it's committed by [`Self::process_block_events`] or
+    /// [`Self::process_upload_code`], without blocking this `Processor` on slow consumers.
+    pub fn subscribe_outcomes(&self) -> OutcomeSubscription {
+        self.notify.subscribe_outcomes()
+    }
+
+    /// Subscribes to committed [`StateTransition`]s, optionally scoped to a single program.
+    pub fn subscribe_transitions(&self, filter: Option<ProgramId>) -> TransitionSubscription {
+        self.notify.subscribe_transitions(filter)
+    }
+
+    /// Switches the pruning strategy used to decide which chain heads' state graphs stay pinned.
+    /// Validators typically want [`PruningMode::Windowed`] to bound disk usage; light/archive
+    /// nodes want [`PruningMode::FullArchive`].
+    pub fn set_pruning_mode(&mut self, mode: PruningMode) {
+        self.gc = gc::RefCountedGc::new(mode);
+    }
+
+    /// Unpins `below_block`'s reachable state graph, returning every hash that became
+    /// unreachable as a result and can be reclaimed from storage.
+    pub fn prune(&mut self, below_block: H256) -> Vec<H256> {
+        self.gc.unpin_head(below_block)
+    }
+
+    /// Builds a [`StateProof`] that `program_id`'s state was `state_hash` at `block_hash`,
+    /// verifiable with [`verify_state_proof`] by a node without the full history. Returns `None`
+    /// if the block's CHT interval hasn't closed yet, or has already fallen below the archival
+    /// window kept by the in-memory [`cht::ChtIndex`].
+    pub fn prove_state_at(&self, program_id: ProgramId, block_hash: H256) -> Option<StateProof> {
+        let sequence = *self.cht_sequence.get(&block_hash)?;
+        self.cht.prove(program_id, sequence)
     }
 
     /// Returns some CodeId in case of settlement and new code accepting.
@@ -206,6 +288,9 @@ impl Processor {
         Ok(self.db.write_state(program_state))
     }
 
+    // TODO: draw from `self.pool_config`'s warm-instance pool instead of instantiating fresh on
+    // every call, once `host::InstanceCreator` exposes a poolable instance type (see
+    // `crate::pool`).
     pub fn run_on_host(
         &mut self,
         program_id: ProgramId,
@@ -249,11 +334,16 @@ impl Processor {
     ) -> Result<Vec<LocalOutcome>> {
         log::debug!("Processing upload code {code_id:?}");
 
-        if code_id != CodeId::generate(code) || self.handle_new_code(code)?.is_none() {
-            Ok(vec![LocalOutcome::CodeRejected(code_id)])
+        let outcome = if code_id != CodeId::generate(code) || self.handle_new_code(code)?.is_none()
+        {
+            LocalOutcome::CodeRejected(code_id)
         } else {
-            Ok(vec![LocalOutcome::CodeApproved(code_id)])
-        }
+            LocalOutcome::CodeApproved(code_id)
+        };
+
+        self.notify.publish(&outcome);
+
+        Ok(vec![outcome])
     }
 
     pub fn process_block_events(
@@ -285,8 +375,23 @@ impl Processor {
 
         let outcomes = self.run(block_hash, &mut states)?;
 
+        let sequence = self.cht_sequence.len() as u32;
+        if let Some((interval, root)) = self.cht.record_block(sequence, &states) {
+            self.cht_storage.put_root(interval, root);
+        }
+        self.cht_sequence.insert(block_hash, sequence);
+
+        // `reclaimable` is only computed here, not deleted: `Storage` has no delete operation in
+        // this crate slice, so these hashes are tracked as unreachable but left on disk.
+        let reclaimable = self.gc.pin_head(&self.db, block_hash, states.values().copied());
+        if !reclaimable.is_empty() {
+            log::debug!("{} state hashes fell out of the pruning window", reclaimable.len());
+        }
+
         self.db.set_block_end_program_states(block_hash, states);
 
+        self.notify.publish_all(&outcomes);
+
         Ok(outcomes)
     }
 
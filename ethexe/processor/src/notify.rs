@@ -0,0 +1,121 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Pub/sub fan-out of [`LocalOutcome`]s to external subscribers (indexers, explorers, RPC
+//! frontends) without the producing [`crate::Processor`] blocking on a slow consumer.
+//!
+//! Backed by [`tokio::sync::broadcast`]: every committed outcome is cloned to each live
+//! subscriber's bounded buffer, and a subscriber that falls too far behind gets
+//! [`tokio::sync::broadcast::error::RecvError::Lagged`] on its next `recv` rather than stalling
+//! the producer or silently growing memory.
+
+use crate::LocalOutcome;
+use gear_core::ids::ProgramId;
+use tokio::sync::broadcast;
+
+/// Buffer depth of both the outcome and transition channels. A subscriber lagging behind by more
+/// than this many outcomes is notified via `RecvError::Lagged` and skips ahead rather than
+/// blocking the producer.
+const NOTIFY_CHANNEL_CAPACITY: usize = 1024;
+
+/// Owns the broadcast sender side of [`Processor`](crate::Processor)'s notification stream and
+/// hands out filtered subscriptions.
+pub struct NotificationHub {
+    outcomes: broadcast::Sender<LocalOutcome>,
+}
+
+impl Default for NotificationHub {
+    fn default() -> Self {
+        let (outcomes, _rx) = broadcast::channel(NOTIFY_CHANNEL_CAPACITY);
+        Self { outcomes }
+    }
+}
+
+impl NotificationHub {
+    /// Fans `outcome` out to every live subscriber. A no-op (not an error) when nobody is
+    /// subscribed.
+    pub fn publish(&self, outcome: &LocalOutcome) {
+        let _ = self.outcomes.send(outcome.clone());
+    }
+
+    /// Convenience over [`Self::publish`] for a batch produced by a single call into
+    /// [`Processor`](crate::Processor).
+    pub fn publish_all(&self, outcomes: &[LocalOutcome]) {
+        for outcome in outcomes {
+            self.publish(outcome);
+        }
+    }
+
+    /// Subscribes to every [`LocalOutcome`] as it's committed.
+    pub fn subscribe_outcomes(&self) -> OutcomeSubscription {
+        OutcomeSubscription {
+            rx: self.outcomes.subscribe(),
+        }
+    }
+
+    /// Subscribes to [`LocalOutcome::Transition`]s, optionally filtered down to a single
+    /// program. `None` subscribes to every program's transitions.
+    pub fn subscribe_transitions(&self, filter: Option<ProgramId>) -> TransitionSubscription {
+        TransitionSubscription {
+            rx: self.outcomes.subscribe(),
+            filter,
+        }
+    }
+}
+
+/// A live subscription to the full [`LocalOutcome`] stream.
+pub struct OutcomeSubscription {
+    rx: broadcast::Receiver<LocalOutcome>,
+}
+
+impl OutcomeSubscription {
+    /// Awaits the next outcome. Returns [`broadcast::error::RecvError::Lagged`] if this
+    /// subscriber fell behind the channel's capacity and missed some outcomes.
+    pub async fn recv(&mut self) -> Result<LocalOutcome, broadcast::error::RecvError> {
+        self.rx.recv().await
+    }
+}
+
+/// A live subscription to [`LocalOutcome::Transition`]s, optionally scoped to one program.
+pub struct TransitionSubscription {
+    rx: broadcast::Receiver<LocalOutcome>,
+    filter: Option<ProgramId>,
+}
+
+impl TransitionSubscription {
+    /// Awaits the next transition matching this subscription's filter, skipping over
+    /// `CodeApproved`/`CodeRejected` outcomes and transitions for other programs.
+    pub async fn recv(&mut self) -> Result<LocalOutcome, broadcast::error::RecvError> {
+        loop {
+            let outcome = self.rx.recv().await?;
+            if self.matches(&outcome) {
+                return Ok(outcome);
+            }
+        }
+    }
+
+    fn matches(&self, outcome: &LocalOutcome) -> bool {
+        let LocalOutcome::Transition(transition) = outcome else {
+            return false;
+        };
+        match self.filter {
+            Some(program_id) => program_id == transition.actor_id,
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,111 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bounded pool of ready-to-use, keyed-by-code resources, so repeated work against the same
+//! `(runtime version, code id)` doesn't pay a fresh setup cost every call.
+//!
+//! [`InstancePool`] is generic over whatever's being pooled: it's the reusable half of the
+//! "cache compiled modules / keep a bounded pool of warm instances" pattern described in
+//! [`crate::Processor::run_on_host`]'s pooling TODO. Wiring it to an actual wasm executor needs
+//! `host::InstanceCreator` to hand out a poolable instance type, which isn't part of this crate
+//! slice yet; until then this module carries the config surface and the cache itself, ready to
+//! take over once that type exists.
+
+use gprimitives::CodeId;
+use std::collections::{BTreeMap, VecDeque};
+
+/// What happens to a pooled instance when it's checked back in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetStrategy {
+    /// Drop it; the next checkout for this key builds a fresh one. Safest default while the
+    /// pooled type's reset behavior (e.g. clearing wasm linear memory) isn't under our control.
+    Recreate,
+    /// Keep it as-is and hand it back out on the next checkout for the same key. Only safe once
+    /// the pooled type resets its own mutable state (memory, globals) between runs.
+    ReuseInPlace,
+}
+
+impl Default for ResetStrategy {
+    fn default() -> Self {
+        Self::Recreate
+    }
+}
+
+/// Configuration for an [`InstancePool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// Maximum idle instances kept per `(runtime version, code id)` key.
+    pub size: usize,
+    pub reset_strategy: ResetStrategy,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            size: 8,
+            reset_strategy: ResetStrategy::default(),
+        }
+    }
+}
+
+/// Bounded pool of idle `T`s, keyed by `(runtime version, code id)`.
+pub struct InstancePool<T> {
+    config: PoolConfig,
+    idle: BTreeMap<(u32, CodeId), VecDeque<T>>,
+}
+
+impl<T> InstancePool<T> {
+    pub fn new(config: PoolConfig) -> Self {
+        Self {
+            config,
+            idle: BTreeMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> PoolConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: PoolConfig) {
+        self.config = config;
+    }
+
+    /// Takes a warm instance for `(runtime_id, code_id)` if one is idle.
+    pub fn checkout(&mut self, runtime_id: u32, code_id: CodeId) -> Option<T> {
+        self.idle.get_mut(&(runtime_id, code_id))?.pop_front()
+    }
+
+    /// Returns an instance after use. Under [`ResetStrategy::Recreate`] this drops it instead of
+    /// keeping it idle. Excess instances beyond [`PoolConfig::size`] are dropped either way.
+    pub fn checkin(&mut self, runtime_id: u32, code_id: CodeId, instance: T) {
+        if self.config.reset_strategy == ResetStrategy::Recreate {
+            return;
+        }
+
+        let idle = self.idle.entry((runtime_id, code_id)).or_default();
+        if idle.len() < self.config.size {
+            idle.push_back(instance);
+        }
+    }
+
+    /// Drops every idle instance for `(runtime_id, code_id)`, e.g. after the code is
+    /// reinstrumented and stale instances would run against an outdated module.
+    pub fn evict(&mut self, runtime_id: u32, code_id: CodeId) {
+        self.idle.remove(&(runtime_id, code_id));
+    }
+}
@@ -0,0 +1,290 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! On-demand [`Storage`] for nodes that don't hold the full state graph.
+//!
+//! Every node the trait deals with is content-addressed, so a local miss can be satisfied by
+//! asking peers for the bytes behind an [`H256`] and checking the hash ourselves before trusting
+//! them — the same on-demand fetching light clients use over a state trie, minus the trie: our
+//! graph is just CAS blobs pointing at other CAS blobs. [`RemoteStorage`] wraps a local
+//! [`Storage`] with a [`PeerClient`] fallback and a small LRU so repeatedly-touched nodes (hot
+//! pages, hot payloads) don't round-trip to the network every read. Every verified remote read is
+//! also written through to `local`, so it's durable past the LRU's eviction and doesn't need
+//! re-fetching after a restart.
+
+use ethexe_runtime_common::state::{Allocations, MemoryPages, MessageQueue, ProgramState, Storage};
+use gear_core::{
+    code::InstrumentedCode, ids::ProgramId, memory::PageBuf, message::Payload,
+    reservation::GasReservationMap,
+};
+use gprimitives::{CodeId, H256};
+use parity_scale_codec::{Decode, Encode};
+use sp_core::blake2_256;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Mutex,
+};
+
+/// Wire messages for the fetch-node-by-hash protocol spoken between [`RemoteStorage`] and peers.
+#[derive(Debug, Clone, Encode, Decode)]
+pub enum NodeRequest {
+    /// Asks a peer for the raw bytes behind a CAS hash.
+    GetNode(H256),
+}
+
+/// Response to a [`NodeRequest::GetNode`]. `None` means the peer doesn't have it either.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct NodeResponse(pub Option<Vec<u8>>);
+
+/// Fetches CAS nodes from the network on a local miss. Implemented by whatever transport the
+/// running node uses (e.g. the `ethexe-network` request/response swarm); blocking here mirrors
+/// [`Storage`] itself being a synchronous, `&self` trait — implementations bridge their own async
+/// I/O internally (e.g. via a `futures`/`tokio` executor handle) rather than pushing `async fn`
+/// through the `Storage` impl.
+pub trait PeerClient: Send + Sync {
+    /// Asks any reachable peer for the node behind `hash`. Returns `None` if no peer has it.
+    fn fetch_node(&self, hash: H256) -> Option<Vec<u8>>;
+}
+
+/// Bounded LRU cache of verified node bytes fetched from peers, so a hot hash that's fetched
+/// repeatedly doesn't re-hit the network each time.
+struct LruCache<K: Ord + Copy, V: Clone> {
+    capacity: usize,
+    entries: BTreeMap<K, V>,
+    order: VecDeque<K>,
+}
+
+impl<K: Ord + Copy, V: Clone> LruCache<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: BTreeMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let value = self.entries.get(key).cloned()?;
+        self.order.retain(|k| k != key);
+        self.order.push_back(*key);
+        Some(value)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.entries.insert(key, value).is_none() {
+            self.order.push_back(key);
+        } else {
+            self.order.retain(|k| k != &key);
+            self.order.push_back(key);
+        }
+        while self.order.len() > self.capacity {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+/// Default number of fetched nodes kept warm in the LRU before the oldest is evicted.
+const DEFAULT_CACHE_CAPACITY: usize = 4096;
+
+/// A [`Storage`] that falls back to fetching missing nodes from peers on read, verifying the
+/// content hash before trusting and caching them. Lets [`crate::Processor::run_on_host`] execute
+/// against a state it only partially holds.
+pub struct RemoteStorage<L: Storage, C: PeerClient> {
+    local: L,
+    client: C,
+    cache: Mutex<LruCache<H256, Vec<u8>>>,
+}
+
+impl<L: Storage, C: PeerClient> RemoteStorage<L, C> {
+    pub fn new(local: L, client: C) -> Self {
+        Self::with_capacity(local, client, DEFAULT_CACHE_CAPACITY)
+    }
+
+    pub fn with_capacity(local: L, client: C, cache_capacity: usize) -> Self {
+        Self {
+            local,
+            client,
+            cache: Mutex::new(LruCache::new(cache_capacity)),
+        }
+    }
+
+    /// Returns the raw bytes behind `hash`, trying the LRU, then the peer network, verifying the
+    /// fetched bytes hash to `hash` before caching them (content addressing means a dishonest or
+    /// confused peer can't substitute a different node).
+    fn fetch(&self, hash: H256) -> Option<Vec<u8>> {
+        if hash.is_zero() {
+            return None;
+        }
+
+        if let Some(cached) = self.cache.lock().expect("cache lock poisoned").get(&hash) {
+            return Some(cached);
+        }
+
+        let bytes = self.client.fetch_node(hash)?;
+        if H256(blake2_256(&bytes)) != hash {
+            log::warn!("peer returned a node whose hash doesn't match the request; dropping it");
+            return None;
+        }
+
+        self.cache
+            .lock()
+            .expect("cache lock poisoned")
+            .insert(hash, bytes.clone());
+        Some(bytes)
+    }
+
+    fn fetch_decoded<T: Decode>(&self, hash: H256) -> Option<T> {
+        T::decode(&mut self.fetch(hash)?.as_slice()).ok()
+    }
+
+    /// Fetches and decodes the node behind `hash`, writing it through to `local` via `write` so a
+    /// remote fetch is a one-time cost rather than something paid again next time the LRU evicts
+    /// it — mirrors what [`Storage::read_pages`] already does for the individual pages it follows.
+    fn fetch_and_store<T: Decode + Clone>(
+        &self,
+        hash: H256,
+        write: impl FnOnce(&L, T) -> H256,
+    ) -> Option<T> {
+        let value = self.fetch_decoded::<T>(hash)?;
+        write(&self.local, value.clone());
+        Some(value)
+    }
+}
+
+impl<L: Storage, C: PeerClient> Storage for RemoteStorage<L, C> {
+    fn clone_boxed(&self) -> Box<dyn Storage> {
+        self.local.clone_boxed()
+    }
+
+    fn read_state(&self, hash: H256) -> Option<ProgramState> {
+        self.local
+            .read_state(hash)
+            .or_else(|| self.fetch_and_store(hash, L::write_state))
+    }
+
+    fn write_state(&self, state: ProgramState) -> H256 {
+        self.local.write_state(state)
+    }
+
+    fn read_queue(&self, hash: H256) -> Option<MessageQueue> {
+        self.local
+            .read_queue(hash)
+            .or_else(|| self.fetch_and_store(hash, L::write_queue))
+    }
+
+    fn write_queue(&self, queue: MessageQueue) -> H256 {
+        self.local.write_queue(queue)
+    }
+
+    /// Reads the pages map, then lazily fetches any individual page hash the map references but
+    /// the local store doesn't have yet — the map itself is small, the pages behind it are not,
+    /// so only the ones actually touched get pulled over the network.
+    fn read_pages(&self, hash: H256) -> Option<MemoryPages> {
+        let pages = self
+            .local
+            .read_pages(hash)
+            .or_else(|| self.fetch_and_store(hash, L::write_pages))?;
+
+        for &page_hash in pages.values() {
+            if self.local.read_page_data(page_hash).is_none() {
+                self.fetch_and_store::<PageBuf>(page_hash, L::write_page_data);
+            }
+        }
+
+        Some(pages)
+    }
+
+    fn write_pages(&self, pages: MemoryPages) -> H256 {
+        self.local.write_pages(pages)
+    }
+
+    fn read_allocations(&self, hash: H256) -> Option<Allocations> {
+        self.local
+            .read_allocations(hash)
+            .or_else(|| self.fetch_and_store(hash, L::write_allocations))
+    }
+
+    fn write_allocations(&self, allocations: Allocations) -> H256 {
+        self.local.write_allocations(allocations)
+    }
+
+    fn read_gas_reservation_map(&self, hash: H256) -> Option<GasReservationMap> {
+        self.local
+            .read_gas_reservation_map(hash)
+            .or_else(|| self.fetch_and_store(hash, L::write_gas_reservation_map))
+    }
+
+    fn write_gas_reservation_map(&self, gas_reservation_map: GasReservationMap) -> H256 {
+        self.local.write_gas_reservation_map(gas_reservation_map)
+    }
+
+    fn get_program_code_id(&self, program_id: ProgramId) -> Option<CodeId> {
+        self.local.get_program_code_id(program_id)
+    }
+
+    fn set_program_code_id(&self, program_id: ProgramId, code_id: CodeId) {
+        self.local.set_program_code_id(program_id, code_id)
+    }
+
+    fn read_original_code(&self, code_id: CodeId) -> Option<Vec<u8>> {
+        self.local.read_original_code(code_id).or_else(|| {
+            let code = self.fetch(H256::from(code_id.into_bytes()))?;
+            self.local.write_original_code(&code);
+            Some(code)
+        })
+    }
+
+    fn write_original_code(&self, code: &[u8]) -> H256 {
+        self.local.write_original_code(code)
+    }
+
+    fn read_instrumented_code(&self, runtime_id: u32, code_id: CodeId) -> Option<InstrumentedCode> {
+        self.local.read_instrumented_code(runtime_id, code_id)
+    }
+
+    fn write_instrumented_code(&self, runtime_id: u32, code_id: CodeId, code: InstrumentedCode) {
+        self.local
+            .write_instrumented_code(runtime_id, code_id, code)
+    }
+
+    fn read_payload(&self, hash: H256) -> Option<Payload> {
+        self.local.read_payload(hash).or_else(|| {
+            let bytes = self.fetch(hash)?;
+            self.local
+                .write_payload(Payload::try_from(bytes.clone()).ok()?);
+            Payload::try_from(bytes).ok()
+        })
+    }
+
+    fn write_payload(&self, payload: Payload) -> H256 {
+        self.local.write_payload(payload)
+    }
+
+    fn read_page_data(&self, hash: H256) -> Option<PageBuf> {
+        self.local
+            .read_page_data(hash)
+            .or_else(|| self.fetch_and_store(hash, L::write_page_data))
+    }
+
+    fn write_page_data(&self, data: PageBuf) -> H256 {
+        self.local.write_page_data(data)
+    }
+}
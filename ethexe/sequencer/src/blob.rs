@@ -0,0 +1,125 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Packs commitment bytes into EIP-4844 blob field elements.
+//!
+//! A blob is [`FIELD_ELEMENTS_PER_BLOB`] 32-byte field elements, each required to be the
+//! canonical encoding of a value less than the BLS12-381 scalar field modulus. The simplest way
+//! to satisfy that without a modular-reduction step is to only ever fill 31 of each element's 32
+//! bytes, leaving the top byte zero — this is the same "31 usable bytes per 32-byte word" layout
+//! most blob-carrying rollups use. Submitting commitments this way moves their bytes out of
+//! calldata (gas-priced per byte) and into blob space (priced separately and far cheaper),
+//! leaving only a KZG commitment/versioned hash in calldata.
+//!
+//! This module only does the byte packing; sending the resulting blob alongside a type-3
+//! transaction needs `ethexe_ethereum::Router` support that isn't part of this crate slice.
+
+/// Field elements in one EIP-4844 blob.
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+/// Usable payload bytes per 32-byte field element (the top byte is always zero).
+const USABLE_BYTES_PER_ELEMENT: usize = 31;
+/// Bytes [`pack_blob`] spends on a little-endian length prefix ahead of `data`, so
+/// [`unpack_blob`] can recover the exact payload length instead of guessing it from trailing
+/// zero bytes (which are indistinguishable from padding when the payload itself ends in zeros).
+const LENGTH_PREFIX_BYTES: usize = 4;
+/// Total payload bytes a single blob can carry.
+pub const MAX_BLOB_PAYLOAD_BYTES: usize =
+    FIELD_ELEMENTS_PER_BLOB * USABLE_BYTES_PER_ELEMENT - LENGTH_PREFIX_BYTES;
+
+use anyhow::{anyhow, Result};
+
+/// Packs `data` into a single blob's worth of field elements, zero-padding the final element.
+/// Returns the blob as `FIELD_ELEMENTS_PER_BLOB * 32` bytes.
+pub fn pack_blob(data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() > MAX_BLOB_PAYLOAD_BYTES {
+        return Err(anyhow!(
+            "{} bytes of commitment data exceed a single blob's {MAX_BLOB_PAYLOAD_BYTES}-byte capacity",
+            data.len()
+        ));
+    }
+
+    let mut prefixed = Vec::with_capacity(LENGTH_PREFIX_BYTES + data.len());
+    prefixed.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    prefixed.extend_from_slice(data);
+
+    let mut blob = vec![0u8; FIELD_ELEMENTS_PER_BLOB * 32];
+    for (chunk, element) in prefixed
+        .chunks(USABLE_BYTES_PER_ELEMENT)
+        .zip(blob.chunks_mut(32))
+    {
+        // Leave element[0] zero so the 32-byte word is always below the scalar field modulus.
+        element[1..1 + chunk.len()].copy_from_slice(chunk);
+    }
+
+    Ok(blob)
+}
+
+/// Inverse of [`pack_blob`]: concatenates each element's 31 payload bytes back together and
+/// trims down to the length recorded in the leading [`LENGTH_PREFIX_BYTES`]-byte header, rather
+/// than guessing it from trailing zero padding.
+pub fn unpack_blob(blob: &[u8]) -> Vec<u8> {
+    let raw: Vec<u8> = blob
+        .chunks(32)
+        .flat_map(|element| element[1..].iter().copied())
+        .collect();
+
+    let len = raw
+        .get(..LENGTH_PREFIX_BYTES)
+        .map(|prefix| u32::from_le_bytes(prefix.try_into().expect("slice is 4 bytes")) as usize)
+        .unwrap_or(0);
+    let end = (LENGTH_PREFIX_BYTES + len).min(raw.len());
+
+    raw.get(LENGTH_PREFIX_BYTES..end)
+        .map(<[u8]>::to_vec)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack_roundtrip() {
+        let data = b"code and block commitments go here".to_vec();
+        let blob = pack_blob(&data).unwrap();
+        assert_eq!(blob.len(), FIELD_ELEMENTS_PER_BLOB * 32);
+        assert_eq!(unpack_blob(&blob), data);
+    }
+
+    #[test]
+    fn test_pack_unpack_roundtrip_with_trailing_zero_byte() {
+        let data = b"commitment data ending in zero\0".to_vec();
+        let blob = pack_blob(&data).unwrap();
+        assert_eq!(unpack_blob(&blob), data);
+    }
+
+    #[test]
+    fn test_pack_rejects_oversized_payload() {
+        let data = vec![1u8; MAX_BLOB_PAYLOAD_BYTES + 1];
+        assert!(pack_blob(&data).is_err());
+    }
+
+    #[test]
+    fn test_pack_fills_every_element_header_byte_with_zero() {
+        let data = vec![0xffu8; MAX_BLOB_PAYLOAD_BYTES];
+        let blob = pack_blob(&data).unwrap();
+        for element in blob.chunks(32) {
+            assert_eq!(element[0], 0);
+        }
+    }
+}
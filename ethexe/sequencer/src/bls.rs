@@ -0,0 +1,266 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! BLS aggregate-signature collection, as an alternative to `agro`'s per-validator ECDSA
+//! `BTreeSet` of signatures — aggregating folds every partial signature into one constant-size
+//! value plus a participation bitfield, so the on-chain payload stops growing with the validator
+//! set.
+//!
+//! The actual curve arithmetic (hash-to-curve on G1, the `e(sigma, g2) == e(H(m), Σ pk_i)`
+//! pairing check) needs a BLS12-381 pairing implementation that isn't a dependency anywhere in
+//! this workspace slice, so it's abstracted behind [`BlsScheme`] rather than hand-rolled here.
+//! [`AggregateCandidate`] itself — the bitfield bookkeeping, the reject-a-second-submission rule,
+//! and the signature-folding sequence — is real and independent of which scheme backs it.
+//!
+//! This module isn't wired into [`crate::Sequencer`] yet: swapping `agro::MultisignedCommitmentDigests`
+//! over to carry an aggregate signature instead of a `BTreeSet` of origins is a change to `agro.rs`,
+//! which isn't part of this workspace slice either.
+
+use anyhow::{anyhow, Result};
+
+/// A fixed-size bitfield over an ordered validator set, tracking which indices have
+/// contributed a partial signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParticipationBitfield {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl ParticipationBitfield {
+    pub fn new(len: usize) -> Self {
+        Self {
+            bits: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    pub fn is_set(&self, index: usize) -> bool {
+        index < self.len && self.bits[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    fn set(&mut self, index: usize) {
+        self.bits[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+}
+
+/// Abstracts the BLS12-381 operations an [`AggregateCandidate`] needs: signing is only used by
+/// tests/callers constructing partial signatures, the candidate itself only verifies and folds.
+pub trait BlsScheme {
+    /// Verifies `signature` over `message` under `public_key`.
+    fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> bool;
+    /// Folds `signatures` into a single aggregate signature.
+    fn aggregate_signatures(&self, signatures: &[&[u8]]) -> Vec<u8>;
+    /// Folds `public_keys` into a single aggregate public key.
+    fn aggregate_public_keys(&self, public_keys: &[&[u8]]) -> Vec<u8>;
+}
+
+/// Collects partial BLS signatures from an ordered validator set toward a single aggregate.
+pub struct AggregateCandidate {
+    validators: Vec<Vec<u8>>,
+    bitfield: ParticipationBitfield,
+    aggregate_signature: Option<Vec<u8>>,
+}
+
+impl AggregateCandidate {
+    pub fn new(validators: Vec<Vec<u8>>) -> Self {
+        let bitfield = ParticipationBitfield::new(validators.len());
+        Self {
+            validators,
+            bitfield,
+            aggregate_signature: None,
+        }
+    }
+
+    pub fn bitfield(&self) -> &ParticipationBitfield {
+        &self.bitfield
+    }
+
+    pub fn aggregate_signature(&self) -> Option<&[u8]> {
+        self.aggregate_signature.as_deref()
+    }
+
+    /// Validates `signature` from the validator at `index` over `message` against its known
+    /// public key, then folds it into the running aggregate. Rejects a second submission from an
+    /// index that has already contributed.
+    pub fn append_signature_with_check(
+        &mut self,
+        scheme: &dyn BlsScheme,
+        index: usize,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<()> {
+        let public_key = self
+            .validators
+            .get(index)
+            .ok_or_else(|| anyhow!("Validator index {index} out of range"))?;
+
+        if self.bitfield.is_set(index) {
+            return Err(anyhow!("Validator at index {index} already signed"));
+        }
+
+        if !scheme.verify(public_key, message, signature) {
+            return Err(anyhow!("Invalid signature from validator index {index}"));
+        }
+
+        self.aggregate_signature = Some(match &self.aggregate_signature {
+            Some(aggregate) => scheme.aggregate_signatures(&[aggregate, signature]),
+            None => signature.to_vec(),
+        });
+        self.bitfield.set(index);
+
+        Ok(())
+    }
+
+    /// Verifies the folded aggregate signature against the aggregate of every participating
+    /// validator's public key.
+    pub fn verify_aggregate(&self, scheme: &dyn BlsScheme, message: &[u8]) -> bool {
+        let Some(aggregate_signature) = &self.aggregate_signature else {
+            return false;
+        };
+
+        let participating_keys: Vec<&[u8]> = self
+            .validators
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.bitfield.is_set(*index))
+            .map(|(_, key)| key.as_slice())
+            .collect();
+
+        let aggregate_public_key = scheme.aggregate_public_keys(&participating_keys);
+        scheme.verify(&aggregate_public_key, message, aggregate_signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// XOR-folding toy scheme, standing in for real pairing-based verification so the bitfield
+    /// and folding logic above can be exercised without a BLS12-381 dependency.
+    struct XorScheme;
+
+    impl BlsScheme for XorScheme {
+        fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+            signature == Self::sign(public_key, message)
+        }
+
+        fn aggregate_signatures(&self, signatures: &[&[u8]]) -> Vec<u8> {
+            Self::xor_all(signatures)
+        }
+
+        fn aggregate_public_keys(&self, public_keys: &[&[u8]]) -> Vec<u8> {
+            Self::xor_all(public_keys)
+        }
+    }
+
+    impl XorScheme {
+        fn sign(public_key: &[u8], message: &[u8]) -> Vec<u8> {
+            Self::xor_all(&[public_key, message])
+        }
+
+        fn xor_all(parts: &[&[u8]]) -> Vec<u8> {
+            let len = parts.iter().map(|p| p.len()).max().unwrap_or(0);
+            let mut out = vec![0u8; len];
+            for part in parts {
+                for (byte, out_byte) in part.iter().zip(out.iter_mut()) {
+                    *out_byte ^= byte;
+                }
+            }
+            out
+        }
+    }
+
+    #[test]
+    fn test_append_signature_rejects_unknown_index() {
+        let scheme = XorScheme;
+        let mut candidate = AggregateCandidate::new(vec![vec![1; 4]]);
+        assert!(candidate
+            .append_signature_with_check(&scheme, 5, b"msg", &[0; 4])
+            .is_err());
+    }
+
+    #[test]
+    fn test_append_signature_rejects_invalid_signature() {
+        let scheme = XorScheme;
+        let mut candidate = AggregateCandidate::new(vec![vec![1; 4]]);
+        assert!(candidate
+            .append_signature_with_check(&scheme, 0, b"msg", &[0xff; 4])
+            .is_err());
+        assert!(!candidate.bitfield().is_set(0));
+    }
+
+    #[test]
+    fn test_append_signature_rejects_double_submission() {
+        let scheme = XorScheme;
+        let public_key = vec![1u8; 4];
+        let message = b"msg";
+        let signature = XorScheme::sign(&public_key, message);
+
+        let mut candidate = AggregateCandidate::new(vec![public_key]);
+        candidate
+            .append_signature_with_check(&scheme, 0, message, &signature)
+            .unwrap();
+        assert!(candidate
+            .append_signature_with_check(&scheme, 0, message, &signature)
+            .is_err());
+    }
+
+    #[test]
+    fn test_aggregate_verifies_once_every_validator_signed() {
+        let scheme = XorScheme;
+        let message = b"commitments digest";
+        let public_keys = vec![vec![1u8; 4], vec![2u8; 4], vec![3u8; 4]];
+
+        let mut candidate = AggregateCandidate::new(public_keys.clone());
+        for (index, public_key) in public_keys.iter().enumerate() {
+            let signature = XorScheme::sign(public_key, message);
+            candidate
+                .append_signature_with_check(&scheme, index, message, &signature)
+                .unwrap();
+        }
+
+        assert_eq!(candidate.bitfield().count_ones(), 3);
+        assert!(candidate.verify_aggregate(&scheme, message));
+    }
+
+    #[test]
+    fn test_aggregate_checks_against_participating_keys_only() {
+        let scheme = XorScheme;
+        let message = b"commitments digest";
+        let public_keys = vec![vec![1u8; 4], vec![2u8; 4]];
+
+        let mut candidate = AggregateCandidate::new(public_keys.clone());
+        let signature = XorScheme::sign(&public_keys[0], message);
+        candidate
+            .append_signature_with_check(&scheme, 0, message, &signature)
+            .unwrap();
+
+        // Verifies against the aggregate of just the one participating key...
+        assert!(candidate.verify_aggregate(&scheme, message));
+        // ...but not against the aggregate of the full validator set, which a caller must compare
+        // the bitfield's participation count against separately to enforce a quorum.
+        let full_set_key = scheme.aggregate_public_keys(
+            &public_keys.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+        );
+        assert!(!scheme.verify(&full_set_key, message, candidate.aggregate_signature().unwrap()));
+    }
+}
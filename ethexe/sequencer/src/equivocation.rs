@@ -0,0 +1,156 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Detects a validator signing two different commitment digests within the same round.
+//!
+//! Each round has exactly one correct candidate digest per commitment kind, so a validator
+//! legitimately only ever signs that one digest. A second, different digest signed by the same
+//! validator before the round resets is equivocation: either the validator is Byzantine, or it's
+//! observing a different chain state than everyone else. Either way it's worth flagging and
+//! penalizing rather than silently accepting the later signature — and, since the on-chain router
+//! is the one who can actually slash for it, the conflicting signatures have to be retained as
+//! evidence rather than just noted and discarded.
+
+use ethexe_signer::{Address, Digest, Signature};
+use gprimitives::H256;
+use std::collections::{btree_map::Entry, BTreeMap};
+
+/// Evidence that `origin` signed two different commitment digests in the same round: both
+/// conflicting signatures, retained so the report can be forwarded to the router for slashing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Equivocation {
+    pub origin: Address,
+    pub round: H256,
+    pub first_digest: Digest,
+    pub second_digest: Digest,
+    pub first_sig: Signature,
+    pub second_sig: Signature,
+}
+
+/// Tracks, for the current round, the single digest (and its signature) each validator has
+/// attested to, and every equivocation caught so far.
+#[derive(Default)]
+pub struct EquivocationTracker {
+    attestations: BTreeMap<Address, (Digest, Signature)>,
+    equivocations: Vec<Equivocation>,
+}
+
+impl EquivocationTracker {
+    /// Records `validator` attesting to `digest` (via `signature`) in `round`. Returns the
+    /// resulting report the moment this validator is caught signing a second, different digest;
+    /// `None` means either this is the validator's first attestation this round, or it matches
+    /// the one already on file.
+    pub fn record(
+        &mut self,
+        validator: Address,
+        round: H256,
+        digest: Digest,
+        signature: Signature,
+    ) -> Option<Equivocation> {
+        match self.attestations.entry(validator) {
+            Entry::Vacant(entry) => {
+                entry.insert((digest, signature));
+                None
+            }
+            Entry::Occupied(entry) => {
+                let (first_digest, first_sig) = *entry.get();
+                if first_digest == digest {
+                    return None;
+                }
+
+                let equivocation = Equivocation {
+                    origin: validator,
+                    round,
+                    first_digest,
+                    second_digest: digest,
+                    first_sig,
+                    second_sig: signature,
+                };
+                self.equivocations.push(equivocation);
+                Some(equivocation)
+            }
+        }
+    }
+
+    /// Every equivocation caught so far. Unlike [`Self::reset`], this history survives a round
+    /// reset, so a caller gets a chance to forward it to the router before it's lost.
+    pub fn equivocations(&self) -> &[Equivocation] {
+        &self.equivocations
+    }
+
+    /// Clears the current round's in-progress attestations, starting a fresh round. Confirmed
+    /// [`Equivocation`] reports are untouched — see [`Self::equivocations`].
+    pub fn reset(&mut self) {
+        self.attestations.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethexe_signer::PrivateKey;
+
+    fn sig(digest: Digest) -> Signature {
+        Signature::create_for_digest(PrivateKey([7; 32]), digest).unwrap()
+    }
+
+    #[test]
+    fn test_record_detects_conflicting_digest() {
+        let mut tracker = EquivocationTracker::default();
+        let validator = Address([1; 20]);
+        let round = H256::zero();
+        let digest1 = Digest::from([1; 32]);
+        let digest2 = Digest::from([2; 32]);
+        let sig1 = sig(digest1);
+        let sig2 = sig(digest2);
+
+        assert_eq!(tracker.record(validator, round, digest1, sig1), None);
+        // Resigning the same digest isn't equivocation.
+        assert_eq!(tracker.record(validator, round, digest1, sig1), None);
+
+        let equivocation = tracker
+            .record(validator, round, digest2, sig2)
+            .expect("signing a second, different digest is equivocation");
+        assert_eq!(equivocation.origin, validator);
+        assert_eq!(equivocation.round, round);
+        assert_eq!(equivocation.first_digest, digest1);
+        assert_eq!(equivocation.second_digest, digest2);
+        assert_eq!(equivocation.first_sig, sig1);
+        assert_eq!(equivocation.second_sig, sig2);
+
+        assert_eq!(tracker.equivocations(), [equivocation]);
+    }
+
+    #[test]
+    fn test_reset_starts_a_fresh_round_but_keeps_equivocation_history() {
+        let mut tracker = EquivocationTracker::default();
+        let validator = Address([1; 20]);
+        let round = H256::zero();
+        let digest1 = Digest::from([1; 32]);
+        let digest2 = Digest::from([2; 32]);
+
+        tracker.record(validator, round, digest1, sig(digest1));
+        let equivocation = tracker
+            .record(validator, round, digest2, sig(digest2))
+            .expect("equivocation");
+        tracker.reset();
+
+        assert_eq!(tracker.record(validator, round, digest2, sig(digest2)), None);
+        assert_eq!(tracker.equivocations(), [equivocation]);
+    }
+}
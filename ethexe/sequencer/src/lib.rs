@@ -19,25 +19,63 @@
 //! Sequencer for ethexe.
 
 pub mod agro;
+pub mod bls;
+pub mod blob;
+pub mod equivocation;
+pub mod merkle;
+pub mod persistence;
+pub mod reputation;
+pub mod validators;
 
 use agro::{AggregatedCommitments, MultisignedCommitmentDigests, MultisignedCommitments};
 use anyhow::{anyhow, Result};
+use equivocation::{Equivocation, EquivocationTracker};
 use ethexe_common::{BlockCommitment, CodeCommitment};
 use ethexe_ethereum::Ethereum;
 use ethexe_observer::Event;
 use ethexe_signer::{Address, AsDigest, Digest, PublicKey, Signature, Signer};
+use gprimitives::H256;
+use merkle::{MerkleMultiProof, MerkleTree};
+use parity_scale_codec::{Decode, Encode};
+use persistence::{InMemoryRoundStorage, RoundKey, RoundStorage, SharedRoundStorage};
+use reputation::{ReputationConfig, ReputationTracker};
 use std::{
-    collections::{BTreeMap, BTreeSet, HashSet},
+    collections::{BTreeMap, BTreeSet},
     ops::Not,
+    sync::Arc,
+    time::Duration,
 };
-use tokio::sync::watch;
+use tokio::{sync::watch, time::Instant};
+use validators::{StakeWeight, ValidatorSet};
 
 pub struct Sequencer {
     key: PublicKey,
     ethereum: Ethereum,
 
-    validators: HashSet<Address>,
-    threshold: u64,
+    validators: ValidatorSet,
+    /// Stake weight a candidate's signing origins must collectively reach to finalize.
+    threshold: StakeWeight,
+    reputation: ReputationTracker,
+    codes_equivocations: EquivocationTracker,
+    blocks_equivocations: EquivocationTracker,
+    /// Whether to submit commitments as EIP-4844 blob-carrying transactions instead of calldata.
+    ///
+    /// Not actually wired up yet: see [`Config::use_blob_transactions`] for why. Setting this to
+    /// `true` only exercises [`blob::pack_blob`] for logging; every commitment still goes out as
+    /// calldata via `router.commit_codes`/`commit_blocks`.
+    use_blob_transactions: bool,
+
+    /// Write-through backend for the current round's aggregated commitments, so a restart can
+    /// resume aggregation instead of losing every collected signature. See [`persistence`].
+    storage: SharedRoundStorage,
+    /// Block hash of the round currently being aggregated; persisted commitments are keyed by
+    /// this so a superseded round's state is never confused with the current one.
+    current_round: H256,
+    /// How long a round stays open for signature collection after its first commitment arrives.
+    collection_window: Duration,
+    /// When the current round's collection window closes, if it's been opened yet. Cleared when
+    /// a new round starts and armed again by the round's first commitment.
+    round_deadline: Option<Instant>,
 
     code_commitments: CommitmentsMap<CodeCommitment>,
     block_commitments: CommitmentsMap<BlockCommitment>,
@@ -53,7 +91,21 @@ pub struct Config {
     pub ethereum_rpc: String,
     pub sign_tx_public: PublicKey,
     pub router_address: Address,
-    pub validators: Vec<Address>,
+    /// Each validator's address and its stake weight for quorum purposes.
+    pub validators: Vec<(Address, StakeWeight)>,
+    /// Submit commitments as EIP-4844 blob-carrying transactions instead of calldata, to cut
+    /// gas costs. See [`crate::blob`].
+    ///
+    /// **Not implemented yet**: `ethexe_ethereum::Router` (outside this crate slice) has no
+    /// blob-transaction variant of `commit_codes`/`commit_blocks` to submit through, so setting
+    /// this to `true` currently changes nothing observable — commitments are still sent as
+    /// calldata every time, same as `false`. Flip it once `Router` grows that support; until then
+    /// it only exists so callers can opt in ahead of time without a breaking config change later.
+    pub use_blob_transactions: bool,
+    /// How long to keep collecting signatures for a round's candidate after its first commitment
+    /// arrives, before [`Sequencer::wait_for_collection_window`] resolves so the driving loop can
+    /// submit whatever quorum was reached by then.
+    pub collection_window: Duration,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -63,17 +115,57 @@ pub struct SequencerStatus {
     pub submitted_block_commitments: u64,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
 struct CommitmentAndOrigins<C> {
     commitment: C,
     origins: BTreeSet<Address>,
 }
 
+impl<C> CommitmentAndOrigins<C> {
+    /// Summed stake weight of everyone who's submitted this commitment so far.
+    fn weight(&self, validators: &ValidatorSet) -> StakeWeight {
+        self.origins
+            .iter()
+            .map(|origin| validators.weight_of(origin))
+            .sum()
+    }
+}
+
 type CommitmentsMap<C> = BTreeMap<Digest, CommitmentAndOrigins<C>>;
 
+/// Minimal stake weight needed to tolerate Byzantine validators holding up to a third of the
+/// total weight `w`: just over two thirds of `w`. Reduces to the familiar headcount-based
+/// `2f + 1` (`n = 3f + 1`) when every validator carries equal weight 1.
+fn bft_threshold(total_weight: StakeWeight) -> StakeWeight {
+    total_weight * 2 / 3 + 1
+}
+
 impl Sequencer {
     pub async fn new(config: &Config, signer: Signer) -> Result<Self> {
         let (status_sender, _status_receiver) = watch::channel(SequencerStatus::default());
+
+        // TODO: back this with the real `ethexe-db`-backed `RoundStorage` once that crate is
+        // available; until then state doesn't actually survive a restart.
+        let storage: SharedRoundStorage = Arc::new(InMemoryRoundStorage::default());
+        // No block has been observed yet this process, so the current round is a sentinel;
+        // reload whatever was persisted under it in case of a crash before the first block.
+        let current_round = H256::zero();
+        let code_commitments =
+            persistence::get(storage.as_ref(), RoundKey::CodeCommitments(current_round))
+                .unwrap_or_default();
+        let block_commitments =
+            persistence::get(storage.as_ref(), RoundKey::BlockCommitments(current_round))
+                .unwrap_or_default();
+        // Resume collecting signatures toward whichever digest set the round had already settled
+        // on as a candidate, rather than rediscovering it from `code_commitments`/
+        // `block_commitments` and potentially picking a different one. The signatures collected
+        // toward it before the restart are lost (see `set_candidate_commitments`'s TODO), so this
+        // only avoids rediscarding already-decided candidates, not re-signing from zero.
+        let codes_candidate: Option<BTreeSet<Digest>> =
+            persistence::get(storage.as_ref(), RoundKey::CodesCandidateDigests(current_round));
+        let blocks_candidate: Option<BTreeSet<Digest>> =
+            persistence::get(storage.as_ref(), RoundKey::BlocksCandidateDigests(current_round));
+
         Ok(Sequencer {
             key: config.sign_tx_public,
             ethereum: Ethereum::new(
@@ -83,12 +175,20 @@ impl Sequencer {
                 config.sign_tx_public.to_address(),
             )
             .await?,
-            validators: config.validators.iter().cloned().collect(),
-            threshold: 1,
-            code_commitments: Default::default(),
-            block_commitments: Default::default(),
-            codes_candidate: Default::default(),
-            blocks_candidate: Default::default(),
+            threshold: bft_threshold(ValidatorSet::new(config.validators.iter().cloned()).total_weight()),
+            validators: ValidatorSet::new(config.validators.iter().cloned()),
+            reputation: ReputationTracker::new(ReputationConfig::default()),
+            codes_equivocations: EquivocationTracker::default(),
+            blocks_equivocations: EquivocationTracker::default(),
+            use_blob_transactions: config.use_blob_transactions,
+            storage,
+            current_round,
+            collection_window: config.collection_window,
+            round_deadline: None,
+            code_commitments,
+            block_commitments,
+            codes_candidate: codes_candidate.map(MultisignedCommitmentDigests::new),
+            blocks_candidate: blocks_candidate.map(MultisignedCommitmentDigests::new),
             status: Default::default(),
             status_sender,
         })
@@ -99,19 +199,66 @@ impl Sequencer {
         if let Event::Block(data) = event {
             log::debug!("Receive block {:?}", data.block_hash);
 
+            if data.block_hash != self.current_round {
+                // The round advanced: whatever was collected for the old round is superseded, so
+                // drop the candidates, the equivocation attestations, the collection-window
+                // deadline, and the old round's persisted commitments (which would only risk
+                // confusing a post-restart reload). New write-throughs land under
+                // `data.block_hash` from here on (see `receive_commitments`).
+                persistence::delete(
+                    self.storage.as_ref(),
+                    RoundKey::CodeCommitments(self.current_round),
+                );
+                persistence::delete(
+                    self.storage.as_ref(),
+                    RoundKey::BlockCommitments(self.current_round),
+                );
+                persistence::delete(
+                    self.storage.as_ref(),
+                    RoundKey::CodesCandidateDigests(self.current_round),
+                );
+                persistence::delete(
+                    self.storage.as_ref(),
+                    RoundKey::BlocksCandidateDigests(self.current_round),
+                );
+                self.current_round = data.block_hash;
+
+                self.codes_candidate = None;
+                self.blocks_candidate = None;
+                self.round_deadline = None;
+
+                self.codes_equivocations.reset();
+                self.blocks_equivocations.reset();
+            }
+
             self.update_status(|status| {
                 *status = SequencerStatus::default();
             });
         }
 
-        // Presently, sequencer resets candidates for each observer event,
-        // because each observer event resets ethexe service rounds.
-        self.codes_candidate = None;
-        self.blocks_candidate = None;
-
         Ok(())
     }
 
+    /// Resolves once the current round's collection window closes, i.e. `collection_window`
+    /// after its first commitment arrived. The driving loop should race this against incoming
+    /// observer events and call [`Self::process_collected_commitments`] (then
+    /// [`Self::submit_multisigned_commitments`]) whenever it resolves, so a threshold-meeting
+    /// batch still gets submitted promptly even if no further events arrive. Never resolves for a
+    /// round that hasn't collected a commitment yet.
+    pub async fn wait_for_collection_window(&self) {
+        match self.round_deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Arms the collection-window deadline if this is the round's first commitment.
+    fn arm_collection_window(&mut self) {
+        if self.round_deadline.is_none() {
+            self.round_deadline = Some(Instant::now() + self.collection_window);
+        }
+    }
+
     pub fn process_collected_commitments(&mut self) -> Result<()> {
         if self.codes_candidate.is_some() || self.blocks_candidate.is_some() {
             return Err(anyhow!("Previous commitments candidate are not submitted"));
@@ -120,13 +267,19 @@ impl Sequencer {
         Self::set_candidate_commitments(
             &self.code_commitments,
             &mut self.codes_candidate,
+            &self.validators,
             self.threshold,
+            self.storage.as_ref(),
+            RoundKey::CodesCandidateDigests(self.current_round),
         );
 
         Self::set_candidate_commitments(
             &self.block_commitments,
             &mut self.blocks_candidate,
+            &self.validators,
             self.threshold,
+            self.storage.as_ref(),
+            RoundKey::BlocksCandidateDigests(self.current_round),
         );
 
         Ok(())
@@ -141,13 +294,21 @@ impl Sequencer {
         let codes_candidate = Self::process_multisigned_candidate(
             &mut self.codes_candidate,
             &mut self.code_commitments,
+            &self.validators,
             self.threshold,
+            self.storage.as_ref(),
+            RoundKey::CodeCommitments(self.current_round),
+            RoundKey::CodesCandidateDigests(self.current_round),
         );
 
         let blocks_candidate = Self::process_multisigned_candidate(
             &mut self.blocks_candidate,
             &mut self.block_commitments,
+            &self.validators,
             self.threshold,
+            self.storage.as_ref(),
+            RoundKey::BlockCommitments(self.current_round),
+            RoundKey::BlocksCandidateDigests(self.current_round),
         );
 
         if let Some(candidate) = codes_candidate {
@@ -192,7 +353,12 @@ impl Sequencer {
             &self.validators,
             self.ethereum.router().address(),
             &mut self.code_commitments,
-        )
+            &mut self.reputation,
+            self.storage.as_ref(),
+            RoundKey::CodeCommitments(self.current_round),
+        )?;
+        self.arm_collection_window();
+        Ok(())
     }
 
     pub fn receive_block_commitments(
@@ -204,7 +370,12 @@ impl Sequencer {
             &self.validators,
             self.ethereum.router().address(),
             &mut self.block_commitments,
-        )
+            &mut self.reputation,
+            self.storage.as_ref(),
+            RoundKey::BlockCommitments(self.current_round),
+        )?;
+        self.arm_collection_window();
+        Ok(())
     }
 
     pub fn receive_codes_signature(&mut self, digest: Digest, signature: Signature) -> Result<()> {
@@ -213,7 +384,10 @@ impl Sequencer {
             signature,
             &self.validators,
             self.ethereum.router().address(),
+            self.current_round,
             self.codes_candidate.as_mut(),
+            &mut self.reputation,
+            &mut self.codes_equivocations,
         )
     }
 
@@ -223,7 +397,10 @@ impl Sequencer {
             signature,
             &self.validators,
             self.ethereum.router().address(),
+            self.current_round,
             self.blocks_candidate.as_mut(),
+            &mut self.reputation,
+            &mut self.blocks_equivocations,
         )
     }
 
@@ -231,6 +408,37 @@ impl Sequencer {
         self.key.to_address()
     }
 
+    /// Applies a new validator set, e.g. after a router event reports a change to it on-chain,
+    /// recomputing the BFT quorum threshold and dropping any in-flight candidate so it can't be
+    /// finalized against a quorum that no longer matches the live set.
+    pub fn update_validators(&mut self, validators: Vec<(Address, StakeWeight)>) {
+        (self.validators, self.threshold) = Self::new_validators_state(validators);
+
+        self.codes_candidate = None;
+        self.blocks_candidate = None;
+    }
+
+    fn new_validators_state(validators: Vec<(Address, StakeWeight)>) -> (ValidatorSet, StakeWeight) {
+        let validators = ValidatorSet::new(validators);
+        let threshold = bft_threshold(validators.total_weight());
+        (validators, threshold)
+    }
+
+    pub fn validators(&self) -> &ValidatorSet {
+        &self.validators
+    }
+
+    /// Every code-commitment-signature equivocation caught so far, for forwarding to the router
+    /// for slashing.
+    pub fn codes_equivocations(&self) -> &[Equivocation] {
+        self.codes_equivocations.equivocations()
+    }
+
+    /// As [`Self::codes_equivocations`], for block-commitment-signature equivocations.
+    pub fn blocks_equivocations(&self) -> &[Equivocation] {
+        self.blocks_equivocations.equivocations()
+    }
+
     pub fn get_status_receiver(&self) -> watch::Receiver<SequencerStatus> {
         self.status_sender.subscribe()
     }
@@ -260,31 +468,102 @@ impl Sequencer {
             })
     }
 
+    /// Root of a Merkle tree over every code commitment digest collected so far this round, so a
+    /// consumer can be shown one commitment was part of the batch via
+    /// [`Self::code_commitments_multiproof`] without needing the whole batch.
+    ///
+    /// TODO: this isn't yet what validators actually sign — `receive_codes_signature` still
+    /// verifies against `agro`'s per-batch digest (`Vec<C>::as_digest`), and switching the signed
+    /// message over to this root is a change to `agro.rs`, which isn't part of this crate slice.
+    pub fn code_commitments_root(&self) -> Option<Digest> {
+        Self::commitments_merkle_tree(&self.code_commitments).root()
+    }
+
+    /// Root of a Merkle tree over every block commitment digest collected so far this round. See
+    /// [`Self::code_commitments_root`] for the same caveat about the signed message.
+    pub fn block_commitments_root(&self) -> Option<Digest> {
+        Self::commitments_merkle_tree(&self.block_commitments).root()
+    }
+
+    /// A multiproof that each of `digests` is one of the code commitments collected so far this
+    /// round, verifiable against [`Self::code_commitments_root`]. `None` if any of `digests` isn't
+    /// actually in the current batch.
+    pub fn code_commitments_multiproof(&self, digests: &[Digest]) -> Option<MerkleMultiProof> {
+        Self::commitments_multiproof(&self.code_commitments, digests)
+    }
+
+    /// A multiproof that each of `digests` is one of the block commitments collected so far this
+    /// round, verifiable against [`Self::block_commitments_root`].
+    pub fn block_commitments_multiproof(&self, digests: &[Digest]) -> Option<MerkleMultiProof> {
+        Self::commitments_multiproof(&self.block_commitments, digests)
+    }
+
+    fn commitments_merkle_tree<C>(commitments: &CommitmentsMap<C>) -> MerkleTree {
+        // `commitments` is a `BTreeMap<Digest, _>`, so its keys already come out sorted.
+        MerkleTree::new(commitments.keys().copied())
+    }
+
+    fn commitments_multiproof<C>(
+        commitments: &CommitmentsMap<C>,
+        digests: &[Digest],
+    ) -> Option<MerkleMultiProof> {
+        let sorted_digests: Vec<Digest> = commitments.keys().copied().collect();
+        let indices = digests
+            .iter()
+            .map(|digest| sorted_digests.binary_search(digest).ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(MerkleTree::new(sorted_digests).multiproof(&indices))
+    }
+
+    // TODO: only the candidate's digest set is persisted here, not the signatures collected
+    // toward it so far, since `agro::MultisignedCommitmentDigests::append_signature_with_check`
+    // (in `agro.rs`) isn't part of this crate slice and so can't be taught to write through to
+    // `storage` itself. A restart still loses in-flight signatures and validators must re-sign,
+    // but at least doesn't lose track of which digests the round settled on as candidates.
     fn set_candidate_commitments<C: AsDigest>(
         commitments: &CommitmentsMap<C>,
         candidate: &mut Option<MultisignedCommitmentDigests>,
-        threshold: u64,
+        validators: &ValidatorSet,
+        threshold: StakeWeight,
+        storage: &dyn RoundStorage,
+        candidate_round_key: RoundKey,
     ) {
         let suitable_digests: BTreeSet<_> = commitments
             .iter()
-            .filter_map(|(digest, c)| (c.origins.len() as u64 >= threshold).then_some(*digest))
+            .filter_map(|(digest, c)| (c.weight(validators) >= threshold).then_some(*digest))
             .collect();
 
         if suitable_digests.is_empty() {
             return;
         }
 
+        persistence::put(storage, candidate_round_key, &suitable_digests);
         *candidate = Some(MultisignedCommitmentDigests::new(suitable_digests));
     }
 
-    fn process_multisigned_candidate<C: AsDigest>(
+    // `candidate.signatures()` already keys each collected signature by the validator `Address`
+    // that produced it, so the threshold can be compared against the summed stake weight of those
+    // signers directly — no change to `agro.rs` is needed for this to be weight-correct.
+    fn process_multisigned_candidate<C: AsDigest + Encode>(
         candidate: &mut Option<MultisignedCommitmentDigests>,
         commitments: &mut CommitmentsMap<C>,
-        threshold: u64,
+        validators: &ValidatorSet,
+        threshold: StakeWeight,
+        storage: &dyn RoundStorage,
+        round_key: RoundKey,
+        candidate_round_key: RoundKey,
     ) -> Option<MultisignedCommitments<C>> {
+        let signers_weight = |c: &MultisignedCommitmentDigests| -> StakeWeight {
+            c.signatures()
+                .keys()
+                .map(|origin| validators.weight_of(origin))
+                .sum()
+        };
+
         if candidate
             .as_ref()
-            .map(|c| threshold > c.signatures().len() as u64)
+            .map(|c| threshold > signers_weight(c))
             .unwrap_or(true)
         {
             return None;
@@ -300,6 +579,13 @@ impl Sequencer {
                 })
         });
 
+        // Re-persist the now-submitted digests' removal alongside the in-memory `remove` above,
+        // so a crash right after this point can't reload and resubmit an already-committed batch.
+        persistence::put(storage, round_key, commitments);
+        // The candidate is finalized, so its persisted digest set would otherwise be replayed
+        // (and re-submitted) on the next restart.
+        persistence::delete(storage, candidate_round_key);
+
         Some(multisigned)
     }
 
@@ -312,6 +598,20 @@ impl Sequencer {
 
         log::debug!("Code commitments to submit: {codes:?}, signed by: {origins:?}",);
 
+        if self.use_blob_transactions {
+            // TODO: submit as an EIP-4844 blob-carrying transaction once `ethexe_ethereum::Router`
+            // grows a blob-transaction variant of `commit_codes`; for now only the payload is
+            // prepared and calldata submission is still used as a fallback.
+            match blob::pack_blob(&codes.encode()) {
+                Ok(blob) => log::debug!(
+                    "Packed {} code commitment bytes into a {}-byte blob for submission",
+                    codes.encode().len(),
+                    blob.len()
+                ),
+                Err(e) => log::warn!("Code commitments don't fit in a single blob: {e}"),
+            }
+        }
+
         let router = self.ethereum.router();
         if let Err(e) = router.commit_codes(codes, signatures).await {
             // TODO: return error?
@@ -330,6 +630,20 @@ impl Sequencer {
 
         log::debug!("Block commitments to submit: {blocks:?}, signed by: {origins:?}",);
 
+        if self.use_blob_transactions {
+            // TODO: submit as an EIP-4844 blob-carrying transaction once `ethexe_ethereum::Router`
+            // grows a blob-transaction variant of `commit_blocks`; for now only the payload is
+            // prepared and calldata submission is still used as a fallback.
+            match blob::pack_blob(&blocks.encode()) {
+                Ok(blob) => log::debug!(
+                    "Packed {} block commitment bytes into a {}-byte blob for submission",
+                    blocks.encode().len(),
+                    blob.len()
+                ),
+                Err(e) => log::warn!("Block commitments don't fit in a single blob: {e}"),
+            }
+        }
+
         let router = self.ethereum.router();
         match router.commit_blocks(blocks, signatures).await {
             Err(e) => {
@@ -346,16 +660,36 @@ impl Sequencer {
         Ok(())
     }
 
-    fn receive_commitments<C: AsDigest>(
+    fn receive_commitments<C: AsDigest + Encode>(
         aggregated: AggregatedCommitments<C>,
-        validators: &HashSet<Address>,
+        validators: &ValidatorSet,
         router_address: Address,
         commitments_storage: &mut CommitmentsMap<C>,
+        reputation: &mut ReputationTracker,
+        storage: &dyn RoundStorage,
+        round_key: RoundKey,
     ) -> Result<()> {
         let origin = aggregated.recover(router_address)?;
 
+        // Non-validators are never given a `reputation` entry at all: `scores` is keyed by
+        // `Address`, and an attacker can recover a commitment to an arbitrary unregistered
+        // address on every call, so faulting unknown origins would let them grow the map without
+        // bound. Only addresses that are actually in `validators` (a bounded set) ever accrue a
+        // score.
         if validators.contains(&origin).not() {
-            return Err(anyhow!("Unknown validator {origin} or invalid signature"));
+            return Err(anyhow!("Unknown validator {origin}"));
+        }
+
+        // `origin`'s signature already verified above, so this is a legitimate submission
+        // regardless of ban status — it earns credit either way, or a banned validator could
+        // never recover (`record_success` would be unreachable forever, see `reputation`'s own
+        // doc comment). What the ban still gates is *this* round's data: a submission that
+        // arrives while banned is rejected even though it nudges the score back toward recovery,
+        // so the ban has to be paid off by one rejected round before the next one is accepted.
+        let was_banned = reputation.is_banned(&origin);
+        reputation.record_success(origin);
+        if was_banned {
+            return Err(anyhow!("Validator {origin} is currently banned"));
         }
 
         for commitment in aggregated.commitments {
@@ -369,15 +703,20 @@ impl Sequencer {
                 .insert(origin);
         }
 
+        persistence::put(storage, round_key, commitments_storage);
+
         Ok(())
     }
 
     fn receive_signature(
         commitments_digest: Digest,
         signature: Signature,
-        validators: &HashSet<Address>,
+        validators: &ValidatorSet,
         router_address: Address,
+        round: H256,
         candidate: Option<&mut MultisignedCommitmentDigests>,
+        reputation: &mut ReputationTracker,
+        equivocations: &mut EquivocationTracker,
     ) -> Result<()> {
         let Some(candidate) = candidate else {
             return Err(anyhow!("No candidate found"));
@@ -388,10 +727,34 @@ impl Sequencer {
             signature,
             router_address,
             |origin| {
-                validators
-                    .contains(&origin)
-                    .then_some(())
-                    .ok_or_else(|| anyhow!("Unknown validator {origin} or invalid signature"))
+                // See `receive_commitments` for why unknown origins are rejected before ever
+                // touching `reputation`: faulting them would let an attacker grow the bounded
+                // `scores` map with addresses recovered from forged signatures.
+                if validators.contains(&origin).not() {
+                    return Err(anyhow!("Unknown validator {origin}"));
+                }
+
+                if let Some(equivocation) =
+                    equivocations.record(origin, round, commitments_digest, signature)
+                {
+                    reputation.record_fault(origin);
+                    return Err(anyhow!(
+                        "Validator {origin} equivocated: signed {commitments_digest} after \
+                         already signing {} this round",
+                        equivocation.first_digest
+                    ));
+                }
+
+                // See `receive_commitments` for why a banned validator's valid signature still
+                // earns `record_success` (so the ban isn't permanent), while the ban still gates
+                // *this* signature's acceptance into the candidate.
+                let was_banned = reputation.is_banned(&origin);
+                reputation.record_success(origin);
+                if was_banned {
+                    return Err(anyhow!("Validator {origin} is currently banned"));
+                }
+
+                Ok(())
             },
         )
     }
@@ -412,7 +775,7 @@ mod tests {
     use anyhow::Ok;
     use ethexe_signer::PrivateKey;
 
-    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Encode)]
     struct TestComm([u8; 2]);
 
     impl AsDigest for TestComm {
@@ -421,6 +784,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bft_threshold() {
+        // n = 3f + 1 validators tolerate f Byzantine ones and need 2f + 1 signatures.
+        assert_eq!(bft_threshold(1), 1);
+        assert_eq!(bft_threshold(4), 3);
+        assert_eq!(bft_threshold(7), 5);
+        assert_eq!(bft_threshold(10), 7);
+    }
+
+    #[test]
+    fn test_new_validators_state() {
+        let signer = Signer::tmp();
+        let addresses: Vec<_> = (1u8..=4)
+            .map(|i| signer.add_key(PrivateKey([i; 32])).unwrap().to_address())
+            .collect();
+
+        let weighted: Vec<_> = addresses.iter().map(|&address| (address, 1)).collect();
+
+        let (validators, threshold) = Sequencer::new_validators_state(weighted.clone());
+        assert_eq!(validators.total_weight(), 4);
+        addresses.iter().for_each(|a| assert!(validators.contains(a)));
+        assert_eq!(threshold, bft_threshold(4));
+
+        let (validators, threshold) = Sequencer::new_validators_state(weighted[..1].to_vec());
+        assert_eq!(validators.total_weight(), 1);
+        assert_eq!(threshold, 1);
+    }
+
     #[test]
     fn test_receive_signature() {
         let signer = Signer::tmp();
@@ -428,16 +819,19 @@ mod tests {
         let router_address = Address([1; 20]);
 
         let validators_private_keys = [PrivateKey([1; 32]), PrivateKey([2; 32])];
-        let validators: HashSet<_> = validators_private_keys
-            .iter()
-            .cloned()
-            .map(|key| signer.add_key(key).unwrap().to_address())
-            .collect();
+        let validators = ValidatorSet::uniform(
+            validators_private_keys
+                .iter()
+                .cloned()
+                .map(|key| signer.add_key(key).unwrap().to_address()),
+        );
 
         let validator1_private_key = validators_private_keys[0];
         let validator1_pub_key = PublicKey::from(validator1_private_key);
         let validator1 = validator1_pub_key.to_address();
 
+        let mut reputation = ReputationTracker::default();
+        let mut equivocations = EquivocationTracker::default();
         let commitments = [TestComm([0, 1]), TestComm([2, 3])];
         let commitments_digest = commitments.as_digest();
         let signature = agro::sign_commitments_digest(
@@ -453,7 +847,10 @@ mod tests {
             signature,
             &validators,
             router_address,
+            H256::zero(),
             None,
+            &mut reputation,
+            &mut equivocations,
         )
         .expect_err("No candidate is provided");
 
@@ -467,7 +864,10 @@ mod tests {
             signature,
             &validators,
             router_address,
+            H256::zero(),
             Some(&mut candidate),
+            &mut reputation,
+            &mut equivocations,
         )
         .expect_err("Incorrect digest has been provided");
 
@@ -476,7 +876,10 @@ mod tests {
             Signature::create_for_digest(validator1_private_key, Digest::from([1; 32])).unwrap(),
             &validators,
             router_address,
+            H256::zero(),
             Some(&mut candidate),
+            &mut reputation,
+            &mut equivocations,
         )
         .expect_err("Signature verification must fail");
 
@@ -485,7 +888,10 @@ mod tests {
             signature,
             &validators,
             router_address,
+            H256::zero(),
             Some(&mut candidate),
+            &mut reputation,
+            &mut equivocations,
         )
         .unwrap();
 
@@ -510,7 +916,10 @@ mod tests {
             signature,
             &validators,
             router_address,
+            H256::zero(),
             Some(&mut candidate),
+            &mut reputation,
+            &mut equivocations,
         )
         .unwrap();
 
@@ -519,6 +928,78 @@ mod tests {
         assert_eq!(&signatures, candidate.signatures());
     }
 
+    #[test]
+    fn test_receive_signature_detects_equivocation() {
+        let signer = Signer::tmp();
+        let router_address = Address([1; 20]);
+
+        let validator_private_key = PrivateKey([1; 32]);
+        let validator_pub_key = signer.add_key(validator_private_key).unwrap();
+        let validator = validator_pub_key.to_address();
+        let validators = ValidatorSet::uniform([validator]);
+
+        let mut reputation = ReputationTracker::default();
+        let mut equivocations = EquivocationTracker::default();
+
+        let digest_a = Digest::from([1; 32]);
+        let digest_b = Digest::from([2; 32]);
+        let mut candidate_a = MultisignedCommitmentDigests::new([digest_a].into_iter().collect());
+        let mut candidate_b = MultisignedCommitmentDigests::new([digest_b].into_iter().collect());
+
+        let signature_a = agro::sign_commitments_digest(
+            digest_a,
+            &signer,
+            validator_pub_key,
+            router_address,
+        )
+        .unwrap();
+        let signature_b = agro::sign_commitments_digest(
+            digest_b,
+            &signer,
+            validator_pub_key,
+            router_address,
+        )
+        .unwrap();
+
+        Sequencer::receive_signature(
+            digest_a,
+            signature_a,
+            &validators,
+            router_address,
+            H256::zero(),
+            Some(&mut candidate_a),
+            &mut reputation,
+            &mut equivocations,
+        )
+        .unwrap();
+
+        Sequencer::receive_signature(
+            digest_b,
+            signature_b,
+            &validators,
+            router_address,
+            H256::zero(),
+            Some(&mut candidate_b),
+            &mut reputation,
+            &mut equivocations,
+        )
+        .expect_err("signing a second, different digest this round is equivocation");
+
+        // Once the round resets, the validator is free to sign again.
+        equivocations.reset();
+        Sequencer::receive_signature(
+            digest_b,
+            signature_b,
+            &validators,
+            router_address,
+            H256::zero(),
+            Some(&mut candidate_b),
+            &mut reputation,
+            &mut equivocations,
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_receive_commitments() {
         let signer = Signer::tmp();
@@ -526,11 +1007,12 @@ mod tests {
         let router_address = Address([1; 20]);
 
         let validators_private_keys = [PrivateKey([1; 32]), PrivateKey([2; 32])];
-        let validators: HashSet<_> = validators_private_keys
-            .iter()
-            .cloned()
-            .map(|key| signer.add_key(key).unwrap().to_address())
-            .collect();
+        let validators = ValidatorSet::uniform(
+            validators_private_keys
+                .iter()
+                .cloned()
+                .map(|key| signer.add_key(key).unwrap().to_address()),
+        );
 
         let validator1_private_key = validators_private_keys[0];
         let validator1_pub_key = PublicKey::from(validator1_private_key);
@@ -547,6 +1029,9 @@ mod tests {
 
         let mut expected_commitments_storage = CommitmentsMap::new();
         let mut commitments_storage = CommitmentsMap::new();
+        let mut reputation = ReputationTracker::default();
+        let storage = InMemoryRoundStorage::default();
+        let round_key = RoundKey::CodeCommitments(H256::zero());
 
         let private_key = PrivateKey([3; 32]);
         let pub_key = signer.add_key(private_key).unwrap();
@@ -562,6 +1047,9 @@ mod tests {
             &validators,
             router_address,
             &mut commitments_storage,
+            &mut reputation,
+            &storage,
+            round_key,
         )
         .expect_err("Signature verification must fail");
 
@@ -570,6 +1058,9 @@ mod tests {
             &validators,
             router_address,
             &mut commitments_storage,
+            &mut reputation,
+            &storage,
+            round_key,
         )
         .unwrap();
 
@@ -606,6 +1097,9 @@ mod tests {
             &validators,
             router_address,
             &mut commitments_storage,
+            &mut reputation,
+            &storage,
+            round_key,
         )
         .unwrap();
 
@@ -622,13 +1116,123 @@ mod tests {
         assert_eq!(expected_commitments_storage, commitments_storage);
     }
 
+    #[test]
+    fn test_receive_commitments_from_unknown_validator_does_not_grow_reputation_map() {
+        let signer = Signer::tmp();
+        let router_address = Address([1; 20]);
+        let validators = ValidatorSet::uniform([signer
+            .add_key(PrivateKey([1; 32]))
+            .unwrap()
+            .to_address()]);
+
+        let mut commitments_storage = CommitmentsMap::new();
+        let mut reputation = ReputationTracker::default();
+        let storage = InMemoryRoundStorage::default();
+        let round_key = RoundKey::CodeCommitments(H256::zero());
+
+        // A forged signature can recover to an arbitrary, never-registered address on every call;
+        // rejecting it must not plant an entry for that address in `reputation`, or an attacker
+        // could grow the map without bound by varying the forged key each time.
+        for i in 0..8u8 {
+            let impostor_pub_key = signer.add_key(PrivateKey([100 + i; 32])).unwrap();
+            let impostor = impostor_pub_key.to_address();
+            let forged = AggregatedCommitments::aggregate_commitments(
+                vec![TestComm([0, 1])],
+                &signer,
+                impostor_pub_key,
+                router_address,
+            )
+            .unwrap();
+
+            Sequencer::receive_commitments(
+                forged,
+                &validators,
+                router_address,
+                &mut commitments_storage,
+                &mut reputation,
+                &storage,
+                round_key,
+            )
+            .expect_err("origin isn't a registered validator");
+
+            assert_eq!(reputation.score(&impostor), 0);
+        }
+    }
+
+    #[test]
+    fn test_receive_commitments_lets_banned_validator_recover() {
+        let signer = Signer::tmp();
+        let router_address = Address([1; 20]);
+        let validator_pub_key = signer.add_key(PrivateKey([1; 32])).unwrap();
+        let validator = validator_pub_key.to_address();
+        let validators = ValidatorSet::uniform([validator]);
+
+        let mut commitments_storage = CommitmentsMap::new();
+        let mut reputation = ReputationTracker::default();
+        let storage = InMemoryRoundStorage::default();
+        let round_key = RoundKey::CodeCommitments(H256::zero());
+
+        // Ban the validator directly, as three equivocations would in practice.
+        reputation.record_fault(validator);
+        reputation.record_fault(validator);
+        reputation.record_fault(validator);
+        assert!(reputation.is_banned(&validator));
+
+        let aggregated = AggregatedCommitments::aggregate_commitments(
+            vec![TestComm([0, 1])],
+            &signer,
+            validator_pub_key,
+            router_address,
+        )
+        .unwrap();
+
+        // A validly-signed submission while still banned is rejected for this round...
+        Sequencer::receive_commitments(
+            aggregated.clone(),
+            &validators,
+            router_address,
+            &mut commitments_storage,
+            &mut reputation,
+            &storage,
+            round_key,
+        )
+        .expect_err("validator is still banned for this round's data");
+        assert!(commitments_storage.is_empty());
+
+        // ...but it's scored as a success rather than further entrenching the ban, so the next
+        // submission from the same validator is accepted again.
+        assert!(!reputation.is_banned(&validator));
+
+        Sequencer::receive_commitments(
+            aggregated,
+            &validators,
+            router_address,
+            &mut commitments_storage,
+            &mut reputation,
+            &storage,
+            round_key,
+        )
+        .expect("validator recovered and is no longer banned");
+        assert!(!commitments_storage.is_empty());
+    }
+
     #[test]
     fn test_set_candidate_commitments() {
+        let validators = ValidatorSet::uniform((1u8..=5).map(|i| Address([i; 20])));
         let mut candidate = None;
         let threshold = 2;
+        let storage = InMemoryRoundStorage::default();
+        let round_key = RoundKey::CodesCandidateDigests(H256::zero());
 
         let mut commitments = BTreeMap::new();
-        Sequencer::set_candidate_commitments(&commitments, &mut candidate, threshold);
+        Sequencer::set_candidate_commitments(
+            &commitments,
+            &mut candidate,
+            &validators,
+            threshold,
+            &storage,
+            round_key,
+        );
         assert!(candidate.is_none());
 
         let commitment1 = TestComm([0, 1]);
@@ -643,7 +1247,14 @@ mod tests {
                     origins: Default::default(),
                 },
             );
-            Sequencer::set_candidate_commitments(&commitments, &mut candidate, threshold);
+            Sequencer::set_candidate_commitments(
+                &commitments,
+                &mut candidate,
+                &validators,
+                threshold,
+                &storage,
+                round_key,
+            );
             assert!(candidate.is_none());
         }
 
@@ -653,7 +1264,14 @@ mod tests {
                 .unwrap()
                 .origins
                 .insert(Address([1; 20]));
-            Sequencer::set_candidate_commitments(&commitments, &mut candidate, threshold);
+            Sequencer::set_candidate_commitments(
+                &commitments,
+                &mut candidate,
+                &validators,
+                threshold,
+                &storage,
+                round_key,
+            );
             assert!(candidate.is_none());
         }
 
@@ -663,7 +1281,14 @@ mod tests {
                 .unwrap()
                 .origins
                 .insert(Address([2; 20]));
-            Sequencer::set_candidate_commitments(&commitments, &mut candidate, threshold);
+            Sequencer::set_candidate_commitments(
+                &commitments,
+                &mut candidate,
+                &validators,
+                threshold,
+                &storage,
+                round_key,
+            );
             let candidate = candidate.as_ref().unwrap();
             assert_eq!(candidate.digests(), [commitment1.as_digest()].as_slice());
             assert!(candidate.signatures().is_empty());
@@ -680,7 +1305,14 @@ mod tests {
                         .collect(),
                 },
             );
-            Sequencer::set_candidate_commitments(&commitments, &mut candidate, threshold);
+            Sequencer::set_candidate_commitments(
+                &commitments,
+                &mut candidate,
+                &validators,
+                threshold,
+                &storage,
+                round_key,
+            );
             let candidate = candidate.as_ref().unwrap();
             assert_eq!(
                 candidate.digests(),
@@ -697,7 +1329,14 @@ mod tests {
                     origins: [Address([5; 20])].iter().cloned().collect(),
                 },
             );
-            Sequencer::set_candidate_commitments(&commitments, &mut candidate, threshold);
+            Sequencer::set_candidate_commitments(
+                &commitments,
+                &mut candidate,
+                &validators,
+                threshold,
+                &storage,
+                round_key,
+            );
             let candidate = candidate.as_ref().unwrap();
             assert_eq!(
                 candidate.digests(),
@@ -712,22 +1351,34 @@ mod tests {
     fn test_process_multisigned_candidate_empty_map() {
         let candidate =
             MultisignedCommitmentDigests::new([[2; 32]].map(Into::into).into_iter().collect());
+        let storage = InMemoryRoundStorage::default();
         Sequencer::process_multisigned_candidate::<TestComm>(
             &mut Some(candidate),
             &mut Default::default(),
+            &ValidatorSet::default(),
             0,
+            &storage,
+            RoundKey::CodeCommitments(H256::zero()),
+            RoundKey::CodesCandidateDigests(H256::zero()),
         );
     }
 
     #[test]
     fn test_process_multisigned_candidate() {
         let signer = Signer::tmp();
+        let storage = InMemoryRoundStorage::default();
+        let round_key = RoundKey::CodeCommitments(H256::zero());
+        let candidate_round_key = RoundKey::CodesCandidateDigests(H256::zero());
 
         // Test candidate is None
         assert!(Sequencer::process_multisigned_candidate::<TestComm>(
             &mut None,
             &mut Default::default(),
-            0
+            &ValidatorSet::default(),
+            0,
+            &storage,
+            round_key,
+            candidate_round_key,
         )
         .is_none());
 
@@ -736,7 +1387,11 @@ mod tests {
         assert!(Sequencer::process_multisigned_candidate(
             &mut candidate,
             &mut CommitmentsMap::<TestComm>::new(),
-            2
+            &ValidatorSet::default(),
+            2,
+            &storage,
+            round_key,
+            candidate_round_key,
         )
         .is_none());
 
@@ -759,7 +1414,15 @@ mod tests {
             );
         });
 
-        Sequencer::set_candidate_commitments(&commitments_map, &mut candidate, 2);
+        let validators = ValidatorSet::uniform(origins.iter().cloned());
+        Sequencer::set_candidate_commitments(
+            &commitments_map,
+            &mut candidate,
+            &validators,
+            2,
+            &storage,
+            candidate_round_key,
+        );
 
         let mut candidate = candidate.expect("Must be set");
         let router_address = Address([1; 20]);
@@ -782,11 +1445,123 @@ mod tests {
         });
 
         let mut candidate = Some(candidate);
-        assert!(
-            Sequencer::process_multisigned_candidate(&mut candidate, &mut commitments_map, 2)
-                .is_some(),
-        );
+        assert!(Sequencer::process_multisigned_candidate(
+            &mut candidate,
+            &mut commitments_map,
+            &validators,
+            2,
+            &storage,
+            round_key,
+            candidate_round_key,
+        )
+        .is_some());
         assert!(commitments_map.is_empty());
         assert!(candidate.is_none());
     }
+
+    #[test]
+    fn test_process_multisigned_candidate_weighs_signers_not_signature_count() {
+        let signer = Signer::tmp();
+        let storage = InMemoryRoundStorage::default();
+        let round_key = RoundKey::CodeCommitments(H256::zero());
+        let candidate_round_key = RoundKey::CodesCandidateDigests(H256::zero());
+
+        // One heavyweight validator (90) and one featherweight validator (10); threshold is 50, so
+        // a signature count of 1 must be enough when it's the heavyweight who signed, even though
+        // a flat headcount of 1 out of 2 wouldn't clear a headcount-equivalent majority.
+        let heavy_pub_key = signer.add_key(PrivateKey([1; 32])).unwrap();
+        let light_pub_key = signer.add_key(PrivateKey([2; 32])).unwrap();
+        let heavy = heavy_pub_key.to_address();
+        let light = light_pub_key.to_address();
+        let validators = ValidatorSet::new([(heavy, 90), (light, 10)]);
+        let threshold = 50;
+
+        let commitment = TestComm([0, 1]);
+        let commitments_digest = vec![commitment].as_digest();
+        let mut candidate =
+            MultisignedCommitmentDigests::new([commitments_digest].into_iter().collect());
+        let router_address = Address([1; 20]);
+
+        candidate
+            .append_signature_with_check(
+                commitments_digest,
+                agro::sign_commitments_digest(
+                    commitments_digest,
+                    &signer,
+                    heavy_pub_key,
+                    router_address,
+                )
+                .unwrap(),
+                router_address,
+                |_| Ok(()),
+            )
+            .unwrap();
+
+        let mut commitments_map = CommitmentsMap::new();
+        commitments_map.insert(
+            commitment.as_digest(),
+            CommitmentAndOrigins {
+                commitment,
+                origins: [heavy].into_iter().collect(),
+            },
+        );
+
+        let mut candidate = Some(candidate);
+        assert!(
+            Sequencer::process_multisigned_candidate(
+                &mut candidate,
+                &mut commitments_map,
+                &validators,
+                threshold,
+                &storage,
+                round_key,
+                candidate_round_key,
+            )
+            .is_some(),
+            "a single signature from the 90-weight validator already clears the 50 threshold"
+        );
+
+        // Now the same single-signature candidate, but signed only by the 10-weight validator:
+        // still one signature, but nowhere near the threshold.
+        let mut candidate =
+            MultisignedCommitmentDigests::new([commitments_digest].into_iter().collect());
+        candidate
+            .append_signature_with_check(
+                commitments_digest,
+                agro::sign_commitments_digest(
+                    commitments_digest,
+                    &signer,
+                    light_pub_key,
+                    router_address,
+                )
+                .unwrap(),
+                router_address,
+                |_| Ok(()),
+            )
+            .unwrap();
+
+        let mut commitments_map = CommitmentsMap::new();
+        commitments_map.insert(
+            commitment.as_digest(),
+            CommitmentAndOrigins {
+                commitment,
+                origins: [light].into_iter().collect(),
+            },
+        );
+
+        let mut candidate = Some(candidate);
+        assert!(
+            Sequencer::process_multisigned_candidate(
+                &mut candidate,
+                &mut commitments_map,
+                &validators,
+                threshold,
+                &storage,
+                round_key,
+                candidate_round_key,
+            )
+            .is_none(),
+            "a single signature from the 10-weight validator doesn't clear the 50 threshold"
+        );
+    }
 }
@@ -0,0 +1,294 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A Merkle tree over commitment digests, so a consumer can be shown that one specific commitment
+//! was part of a signed batch without needing the whole batch.
+//!
+//! Leaves are the batch's commitment digests in sorted order; each parent is the hash of its two
+//! children concatenated, with an odd node at a level duplicated against itself rather than left
+//! unpaired. [`MerkleTree::multiproof`] returns the minimal set of sibling hashes needed to verify
+//! several leaves against the root in one pass, reusing shared internal nodes the way a batched
+//! on-chain inclusion check would.
+
+use ethexe_signer::{AsDigest, Digest};
+
+/// Hashes two child nodes into their parent, the same way the rest of this crate turns data into
+/// a [`Digest`] via [`AsDigest`].
+fn hash_pair(left: Digest, right: Digest) -> Digest {
+    [left, right].as_digest()
+}
+
+/// Number of nodes one level up from a level with `len` nodes, duplicating an unpaired last node.
+fn parent_len(len: usize) -> usize {
+    len.div_ceil(2)
+}
+
+/// A Merkle tree over a batch's commitment digests.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    /// `layers[0]` is the sorted leaves; each subsequent layer is the parent hashes of the one
+    /// below, ending with a single-element layer holding the root.
+    layers: Vec<Vec<Digest>>,
+}
+
+impl MerkleTree {
+    /// Builds a tree over `leaves`, sorting them first so the root only depends on the leaf set,
+    /// not the order commitments happened to be collected in.
+    pub fn new(leaves: impl IntoIterator<Item = Digest>) -> Self {
+        let mut leaves: Vec<_> = leaves.into_iter().collect();
+        leaves.sort();
+
+        let mut layers = vec![leaves];
+        while layers.last().map(Vec::len).unwrap_or(0) > 1 {
+            let level = layers.last().unwrap();
+            let next = (0..parent_len(level.len()))
+                .map(|i| {
+                    let left = level[2 * i];
+                    let right = level.get(2 * i + 1).copied().unwrap_or(left);
+                    hash_pair(left, right)
+                })
+                .collect();
+            layers.push(next);
+        }
+
+        Self { layers }
+    }
+
+    /// The tree's root, or `None` for an empty batch.
+    pub fn root(&self) -> Option<Digest> {
+        self.layers.last()?.first().copied()
+    }
+
+    /// Builds a multiproof for the leaves at `indices` into the (sorted) leaf set this tree was
+    /// built from.
+    pub fn multiproof(&self, indices: &[usize]) -> MerkleMultiProof {
+        let mut known: Vec<usize> = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+
+        let mut proof = Vec::new();
+        let mut proof_flags = Vec::new();
+
+        for level in &self.layers[..self.layers.len().saturating_sub(1)] {
+            let mut next_known = Vec::new();
+
+            let mut i = 0;
+            while i < known.len() {
+                let index = known[i];
+                let sibling = index ^ 1;
+                let sibling_known = known.get(i + 1).copied() == Some(sibling);
+
+                if sibling_known {
+                    i += 2;
+                } else {
+                    let sibling_digest = level.get(sibling).copied().unwrap_or(level[index]);
+                    proof.push(sibling_digest);
+                    i += 1;
+                }
+                proof_flags.push(sibling_known);
+
+                next_known.push(index / 2);
+            }
+
+            next_known.dedup();
+            known = next_known;
+        }
+
+        MerkleMultiProof {
+            leaf_count: self.layers[0].len(),
+            proof,
+            proof_flags,
+        }
+    }
+}
+
+/// A multiproof of inclusion for several leaves in a [`MerkleTree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleMultiProof {
+    /// Number of leaves in the tree the proof was generated from, needed to know each level's
+    /// size (and so which nodes are duplicated-last padding) while verifying.
+    leaf_count: usize,
+    /// Sibling hashes not derivable from the leaves being proven, in the order they're consumed
+    /// while walking up the tree.
+    proof: Vec<Digest>,
+    /// For each combination step while walking up the tree (in the same order as `proof` would be
+    /// consumed): `true` if both children were already known (no proof hash needed), `false` if
+    /// the next entry of `proof` supplies the sibling.
+    proof_flags: Vec<bool>,
+}
+
+/// Verifies that `leaves` (sorted-index, digest pairs into the original, sorted leaf set) are all
+/// included in the tree committed to by `root`, given `proof`.
+pub fn verify_multiproof(root: Digest, leaves: &[(usize, Digest)], proof: &MerkleMultiProof) -> bool {
+    let mut known: Vec<(usize, Digest)> = leaves.to_vec();
+    known.sort_unstable_by_key(|(index, _)| *index);
+
+    let mut level_len = proof.leaf_count;
+    let mut proof_iter = proof.proof.iter();
+    let mut flags_iter = proof.proof_flags.iter();
+
+    while known.len() > 1 || level_len > 1 {
+        let mut next_known = Vec::new();
+
+        let mut i = 0;
+        while i < known.len() {
+            let (index, digest) = known[i];
+            let sibling = index ^ 1;
+
+            let Some(&uses_known_sibling) = flags_iter.next() else {
+                return false;
+            };
+
+            let sibling_digest = if uses_known_sibling {
+                let Some(&(sibling_index, sibling_digest)) = known.get(i + 1) else {
+                    return false;
+                };
+                if sibling_index != sibling {
+                    return false;
+                }
+                i += 2;
+                sibling_digest
+            } else {
+                let Some(&sibling_digest) = proof_iter.next() else {
+                    return false;
+                };
+                i += 1;
+                sibling_digest
+            };
+
+            let (left, right) = if index % 2 == 0 {
+                (digest, sibling_digest)
+            } else {
+                (sibling_digest, digest)
+            };
+
+            // An odd node out at this level is duplicated against itself rather than paired; the
+            // sibling we just consumed should equal `digest` in that case.
+            let parent = if sibling >= level_len {
+                hash_pair(digest, digest)
+            } else {
+                hash_pair(left, right)
+            };
+
+            next_known.push((index / 2, parent));
+        }
+
+        next_known.dedup_by_key(|(index, _)| *index);
+        known = next_known;
+        level_len = parent_len(level_len);
+    }
+
+    proof_iter.next().is_none() && known.first().map(|(_, digest)| *digest) == Some(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(byte: u8) -> Digest {
+        Digest::from([byte; 32])
+    }
+
+    #[test]
+    fn test_root_is_none_for_empty_tree() {
+        let tree = MerkleTree::new([]);
+        assert_eq!(tree.root(), None);
+    }
+
+    #[test]
+    fn test_root_is_the_single_leaf_for_a_singleton_tree() {
+        let leaf = digest(1);
+        let tree = MerkleTree::new([leaf]);
+        assert_eq!(tree.root(), Some(leaf));
+    }
+
+    #[test]
+    fn test_root_is_order_independent() {
+        let leaves = [digest(1), digest(2), digest(3), digest(4)];
+        let forward = MerkleTree::new(leaves);
+        let reversed = MerkleTree::new(leaves.into_iter().rev());
+        assert_eq!(forward.root(), reversed.root());
+    }
+
+    #[test]
+    fn test_multiproof_verifies_a_single_leaf() {
+        let leaves: Vec<_> = (1..=5).map(digest).collect();
+        let mut sorted = leaves.clone();
+        sorted.sort();
+
+        let tree = MerkleTree::new(leaves);
+        let root = tree.root().unwrap();
+
+        for (index, &leaf) in sorted.iter().enumerate() {
+            let proof = tree.multiproof(&[index]);
+            assert!(verify_multiproof(root, &[(index, leaf)], &proof));
+        }
+    }
+
+    #[test]
+    fn test_multiproof_verifies_several_leaves_at_once() {
+        let leaves: Vec<_> = (1..=7).map(digest).collect();
+        let mut sorted = leaves.clone();
+        sorted.sort();
+
+        let tree = MerkleTree::new(leaves);
+        let root = tree.root().unwrap();
+
+        let indices = [1, 2, 5];
+        let proof = tree.multiproof(&indices);
+        let proven: Vec<_> = indices.iter().map(|&i| (i, sorted[i])).collect();
+
+        assert!(verify_multiproof(root, &proven, &proof));
+    }
+
+    #[test]
+    fn test_multiproof_rejects_a_wrong_leaf() {
+        let leaves: Vec<_> = (1..=4).map(digest).collect();
+        let tree = MerkleTree::new(leaves);
+        let root = tree.root().unwrap();
+
+        let proof = tree.multiproof(&[0]);
+        assert!(!verify_multiproof(root, &[(0, digest(99))], &proof));
+    }
+
+    #[test]
+    fn test_multiproof_rejects_against_wrong_root() {
+        let leaves: Vec<_> = (1..=4).map(digest).collect();
+        let mut sorted = leaves.clone();
+        sorted.sort();
+
+        let tree = MerkleTree::new(leaves);
+        let proof = tree.multiproof(&[0]);
+
+        assert!(!verify_multiproof(digest(123), &[(0, sorted[0])], &proof));
+    }
+
+    #[test]
+    fn test_odd_leaf_count_duplicates_the_last_leaf() {
+        let leaves: Vec<_> = (1..=3).map(digest).collect();
+        let mut sorted = leaves.clone();
+        sorted.sort();
+
+        let tree = MerkleTree::new(leaves);
+        let expected_root = hash_pair(
+            hash_pair(sorted[0], sorted[1]),
+            hash_pair(sorted[2], sorted[2]),
+        );
+        assert_eq!(tree.root(), Some(expected_root));
+    }
+}
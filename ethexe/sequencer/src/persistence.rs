@@ -0,0 +1,129 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Write-through persistence for the current round's aggregated commitments and candidates.
+//!
+//! Without this, a sequencer restart loses every commitment and partial signature collected so
+//! far, forcing validators to re-sign from scratch. [`RoundStorage`] is a small key-value
+//! boundary rather than a direct `ethexe-db` dependency: the real `ethexe-db` crate isn't part
+//! of this workspace slice, so [`Sequencer`](crate::Sequencer) is wired against this trait
+//! instead, with [`InMemoryRoundStorage`] standing in until a genuine `ethexe-db`-backed
+//! implementation can be plugged in.
+
+use gprimitives::H256;
+use parity_scale_codec::{Decode, Encode};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+/// Keys under which round state is stored, namespaced by the round's block hash so state from a
+/// superseded round is never confused with the current one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Encode)]
+pub(crate) enum RoundKey {
+    CodeCommitments(H256),
+    BlockCommitments(H256),
+    /// Digests of an in-flight codes candidate awaiting enough signatures. Only the digest set is
+    /// persisted, not the signatures collected toward it so far — see
+    /// [`crate::Sequencer::receive_codes_signature`].
+    CodesCandidateDigests(H256),
+    /// As [`RoundKey::CodesCandidateDigests`], for an in-flight blocks candidate.
+    BlocksCandidateDigests(H256),
+}
+
+/// A minimal write-through key-value boundary for round state, standing in for an `ethexe-db`
+/// backend.
+pub trait RoundStorage: Send + Sync {
+    fn put(&self, key: Vec<u8>, value: Vec<u8>);
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
+    fn delete(&self, key: &[u8]);
+}
+
+/// Persists `value` under `key`, scale-encoded.
+pub(crate) fn put<T: Encode>(storage: &dyn RoundStorage, key: RoundKey, value: &T) {
+    storage.put(key.encode(), value.encode());
+}
+
+/// Loads and decodes the value stored under `key`, if any.
+pub(crate) fn get<T: Decode>(storage: &dyn RoundStorage, key: RoundKey) -> Option<T> {
+    storage
+        .get(&key.encode())
+        .and_then(|bytes| T::decode(&mut bytes.as_slice()).ok())
+}
+
+/// Deletes whatever is stored under `key`, if anything.
+pub(crate) fn delete(storage: &dyn RoundStorage, key: RoundKey) {
+    storage.delete(&key.encode());
+}
+
+/// In-memory [`RoundStorage`], used until a real `ethexe-db`-backed implementation exists.
+/// Naturally loses all state across a restart, so it doesn't actually provide crash recovery by
+/// itself — it only exercises the write-through/reload code paths.
+#[derive(Default)]
+pub struct InMemoryRoundStorage {
+    entries: Mutex<BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl RoundStorage for InMemoryRoundStorage {
+    fn put(&self, key: Vec<u8>, value: Vec<u8>) {
+        self.entries.lock().unwrap().insert(key, value);
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.entries.lock().unwrap().get(key).cloned()
+    }
+
+    fn delete(&self, key: &[u8]) {
+        self.entries.lock().unwrap().remove(key);
+    }
+}
+
+/// Shared handle to a [`RoundStorage`] implementation.
+pub type SharedRoundStorage = Arc<dyn RoundStorage>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_get_delete_roundtrip() {
+        let storage = InMemoryRoundStorage::default();
+        let key = RoundKey::CodeCommitments(H256::zero());
+
+        assert_eq!(get::<u32>(&storage, key), None);
+
+        put(&storage, key, &42u32);
+        assert_eq!(get::<u32>(&storage, key), Some(42));
+
+        delete(&storage, key);
+        assert_eq!(get::<u32>(&storage, key), None);
+    }
+
+    #[test]
+    fn test_keys_for_different_rounds_dont_collide() {
+        let storage = InMemoryRoundStorage::default();
+        let round_a = RoundKey::CodeCommitments(H256::zero());
+        let round_b = RoundKey::CodeCommitments(H256::from([1; 32]));
+
+        put(&storage, round_a, &1u32);
+        put(&storage, round_b, &2u32);
+
+        assert_eq!(get::<u32>(&storage, round_a), Some(1));
+        assert_eq!(get::<u32>(&storage, round_b), Some(2));
+    }
+}
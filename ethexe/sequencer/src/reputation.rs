@@ -0,0 +1,138 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Validator reputation tracking.
+//!
+//! Down-weights (and, past a threshold, temporarily bans) misbehaving validators. The
+//! [`crate::Sequencer`] records a fault only when a validator equivocates (signs two different
+//! digests in one round); submissions rejected for an unknown or unrecoverable signer are not
+//! attributed to any validator and cost no score. A ban is score-based rather than
+//! wall-clock based: it lifts itself as the validator accrues successes again, so a validator
+//! that was flaky for a round isn't locked out forever, but one that keeps misbehaving stays
+//! banned until it stops.
+
+use ethexe_signer::Address;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReputationConfig {
+    /// Score deducted for a rejected commitment or signature.
+    pub fault_penalty: i32,
+    /// Score restored for a successfully accepted commitment or signature.
+    pub success_reward: i32,
+    /// A validator is banned once its score drops to or below `-ban_threshold`.
+    pub ban_threshold: i32,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        Self {
+            fault_penalty: 10,
+            success_reward: 1,
+            ban_threshold: 30,
+        }
+    }
+}
+
+/// Per-validator reputation score, clamped to `[-ban_threshold, 0]` so a long streak of good
+/// behavior can't bank credit against a future fault.
+#[derive(Default)]
+pub struct ReputationTracker {
+    config: ReputationConfig,
+    scores: BTreeMap<Address, i32>,
+}
+
+impl ReputationTracker {
+    pub fn new(config: ReputationConfig) -> Self {
+        Self {
+            config,
+            scores: BTreeMap::new(),
+        }
+    }
+
+    /// Penalizes `validator` for a rejected commitment or signature.
+    pub fn record_fault(&mut self, validator: Address) {
+        let score = self.scores.entry(validator).or_insert(0);
+        *score = (*score - self.config.fault_penalty).max(-self.config.ban_threshold);
+    }
+
+    /// Rewards `validator` for a successfully accepted commitment or signature, nudging it back
+    /// toward (but never above) a clean score.
+    pub fn record_success(&mut self, validator: Address) {
+        let score = self.scores.entry(validator).or_insert(0);
+        *score = (*score + self.config.success_reward).min(0);
+    }
+
+    /// Whether `validator`'s score has dropped to or below the ban threshold.
+    pub fn is_banned(&self, validator: &Address) -> bool {
+        self.score(validator) <= -self.config.ban_threshold
+    }
+
+    pub fn score(&self, validator: &Address) -> i32 {
+        self.scores.get(validator).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_fault_bans_past_threshold() {
+        let mut tracker = ReputationTracker::new(ReputationConfig {
+            fault_penalty: 10,
+            success_reward: 1,
+            ban_threshold: 25,
+        });
+        let validator = Address([1; 20]);
+
+        assert!(!tracker.is_banned(&validator));
+
+        tracker.record_fault(validator);
+        tracker.record_fault(validator);
+        assert!(!tracker.is_banned(&validator));
+
+        tracker.record_fault(validator);
+        assert!(tracker.is_banned(&validator));
+    }
+
+    #[test]
+    fn test_record_success_lifts_a_ban_over_time() {
+        let mut tracker = ReputationTracker::new(ReputationConfig {
+            fault_penalty: 10,
+            success_reward: 5,
+            ban_threshold: 10,
+        });
+        let validator = Address([1; 20]);
+
+        tracker.record_fault(validator);
+        assert!(tracker.is_banned(&validator));
+
+        tracker.record_success(validator);
+        assert!(!tracker.is_banned(&validator));
+    }
+
+    #[test]
+    fn test_score_never_exceeds_zero() {
+        let mut tracker = ReputationTracker::new(ReputationConfig::default());
+        let validator = Address([1; 20]);
+
+        tracker.record_success(validator);
+        assert_eq!(tracker.score(&validator), 0);
+    }
+}
@@ -0,0 +1,94 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A validator set where each validator carries a stake weight, so quorum can be decided by
+//! summed weight rather than by a flat headcount — the right notion of "enough validators agreed"
+//! for a proof-of-stake router where validators aren't all equally weighted.
+
+use ethexe_signer::Address;
+use std::collections::BTreeMap;
+
+/// A validator's voting power. Plain `u64` rather than a balance type since the router is the
+/// source of truth for what a unit of weight represents (stake, delegated stake, etc.).
+pub type StakeWeight = u64;
+
+/// Maps each validator to its stake weight.
+#[derive(Debug, Clone, Default)]
+pub struct ValidatorSet {
+    weights: BTreeMap<Address, StakeWeight>,
+}
+
+impl ValidatorSet {
+    pub fn new(entries: impl IntoIterator<Item = (Address, StakeWeight)>) -> Self {
+        Self {
+            weights: entries.into_iter().collect(),
+        }
+    }
+
+    /// Builds a set where every address carries equal weight, for callers that only have a flat
+    /// validator list and want headcount-equivalent behavior.
+    pub fn uniform(addresses: impl IntoIterator<Item = Address>) -> Self {
+        Self::new(addresses.into_iter().map(|address| (address, 1)))
+    }
+
+    pub fn contains(&self, address: &Address) -> bool {
+        self.weights.contains_key(address)
+    }
+
+    pub fn weight_of(&self, address: &Address) -> StakeWeight {
+        self.weights.get(address).copied().unwrap_or(0)
+    }
+
+    pub fn total_weight(&self) -> StakeWeight {
+        self.weights.values().sum()
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = &Address> {
+        self.weights.keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uniform_set_totals_headcount() {
+        let addresses = [Address([1; 20]), Address([2; 20]), Address([3; 20])];
+        let set = ValidatorSet::uniform(addresses);
+        assert_eq!(set.total_weight(), 3);
+        assert_eq!(set.weight_of(&addresses[0]), 1);
+    }
+
+    #[test]
+    fn test_weighted_set_sums_explicit_weights() {
+        let a = Address([1; 20]);
+        let b = Address([2; 20]);
+        let set = ValidatorSet::new([(a, 10), (b, 30)]);
+        assert_eq!(set.total_weight(), 40);
+        assert_eq!(set.weight_of(&a), 10);
+        assert_eq!(set.weight_of(&b), 30);
+    }
+
+    #[test]
+    fn test_weight_of_unknown_validator_is_zero() {
+        let set = ValidatorSet::new([(Address([1; 20]), 10)]);
+        assert_eq!(set.weight_of(&Address([2; 20])), 0);
+        assert!(!set.contains(&Address([2; 20])));
+    }
+}
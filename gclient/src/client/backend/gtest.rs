@@ -19,34 +19,29 @@
 use crate::client::{Backend, Code, Message, Program, TxResult};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use gear_core::{ids::ProgramId, message::UserStoredMessage};
+use gear_core::{
+    ids::ProgramId,
+    message::{UserMessage, UserStoredMessage},
+};
 use gprimitives::{ActorId, MessageId};
 use gsdk::metadata::runtime_types::gear_common::storage::primitives::Interval;
 use gtest::System;
-use std::{
-    collections::HashMap,
-    sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc,
-    },
-    time::{Duration, SystemTime},
-};
+use std::{sync::Arc, time::Duration};
 use tokio::{
     sync::{
         mpsc::{self, Sender},
-        Mutex,
+        oneshot,
     },
     task::{JoinHandle, LocalSet},
+    time::timeout,
 };
 
 /// gear general client gtest backend
 #[derive(Clone)]
 pub struct GTest {
     tx: Sender<Request>,
-    results: Arc<Mutex<HashMap<usize, Response>>>,
     timeout: Duration,
     _handle: Arc<JoinHandle<()>>,
-    nonce: Arc<AtomicUsize>,
 }
 
 impl GTest {
@@ -60,65 +55,111 @@ impl GTest {
     /// method if the paramters bother you.
     pub fn new(size: usize, timeout: Duration) -> Self {
         let local = LocalSet::new();
-        let results = Arc::new(Mutex::new(HashMap::new()));
         let (tx, mut rx) = mpsc::channel::<Request>(size);
 
-        let cloned = results.clone();
         let handle = local.spawn_local(async move {
             let system = System::new();
-            while let Some(tx) = rx.recv().await {
-                let (result, nounce) = match tx {
+            while let Some(request) = rx.recv().await {
+                match request {
                     Request::Deploy {
-                        nonce,
                         code,
                         message,
                         signer,
-                    } => (handle::deploy(&system, code, message, signer), nonce),
+                        reply,
+                    } => {
+                        let _ = reply.send(handle::deploy(&system, code, message, signer));
+                    }
                     Request::Send {
-                        nonce,
                         prog,
                         message,
                         signer,
-                    } => (handle::send(&system, prog, message, signer), nonce),
-                    Request::Program { nonce, id } => (handle::prog(&system, id), nonce),
-                };
-
-                cloned.lock().await.insert(nounce, result);
+                        reply,
+                    } => {
+                        let _ = reply.send(handle::send(&system, prog, message, signer));
+                    }
+                    Request::Program { id, reply } => {
+                        let _ = reply.send(handle::prog(&system, id));
+                    }
+                    Request::Mailbox { id, reply } => {
+                        let _ = reply.send(handle::mailbox(&system, id));
+                    }
+                    Request::Message { mid, reply } => {
+                        let _ = reply.send(handle::message(&system, mid));
+                    }
+                    Request::Claim { mid, reply } => {
+                        let _ = reply.send(handle::claim(&system, mid));
+                    }
+                }
             }
         });
 
         Self {
             tx,
-            results,
             timeout,
-            nonce: Arc::new(AtomicUsize::new(0)),
             _handle: Arc::new(handle),
         }
     }
 
-    /// Get gtest result from nonce.
-    async fn resp(&self, nonce: usize) -> Result<Response> {
-        let now = SystemTime::now();
+    /// Send `request` to the gtest event loop and await its reply, bounded by `self.timeout`.
+    async fn resp(
+        &self,
+        request: Request,
+        receiver: oneshot::Receiver<Response>,
+    ) -> Result<Response> {
+        self.tx.send(request).await?;
+
+        timeout(self.timeout, receiver)
+            .await
+            .map_err(|_| anyhow!("gtest: Transaction timed out!"))?
+            .map_err(|_| anyhow!("gtest: event loop dropped the reply channel"))
+    }
 
-        loop {
-            if now.elapsed()? > self.timeout {
-                return Err(anyhow!("gtest: Transaction timed out!"));
-            }
+    /// Mailbox entries currently queued for `id`.
+    ///
+    /// Exposed as an inherent method (rather than through `Backend`, which only declares a
+    /// single-message lookup and, per [`Backend::message`]'s doc, doesn't route through this
+    /// yet) so it's reachable for callers willing to accept its caveat: see
+    /// [`handle::mailbox`]'s doc comment for why this is an unverified guess at `gtest::System`'s
+    /// real API rather than a checked call.
+    pub async fn mailbox(&self, id: ActorId) -> Result<Vec<(UserStoredMessage, Interval<u32>)>> {
+        let (reply, receiver) = oneshot::channel();
+
+        let result = self.resp(Request::Mailbox { id, reply }, receiver).await?;
+        let Response::Mailbox(result) = result else {
+            return Err(anyhow!(
+                "Response is not matched with mailbox request, {result:?}"
+            ));
+        };
 
-            if let Some(resp) = self.results.lock().await.remove(&nonce) {
-                return Ok(resp);
-            }
-        }
+        Ok(result)
+    }
+
+    /// Claim the value attached to mailbox message `mid`.
+    ///
+    /// Would be `Backend::claim_value` so gtest and the node-backed client expose the same
+    /// mailbox semantics, but `Backend` lives outside this crate snapshot, so it's an inherent
+    /// method here until that trait gains the method. Carries the same unverified-API caveat as
+    /// [`Self::mailbox`].
+    pub async fn claim_value(&self, mid: MessageId) -> Result<TxResult<()>> {
+        let (reply, receiver) = oneshot::channel();
+
+        let result = self.resp(Request::Claim { mid, reply }, receiver).await?;
+        let Response::Claim(result) = result else {
+            return Err(anyhow!(
+                "Response is not matched with claim request, {result:?}"
+            ));
+        };
+
+        Ok(result)
     }
 }
 
 #[async_trait]
 impl Backend for GTest {
     async fn program(&self, id: ProgramId) -> Result<Program<Self>> {
-        let nonce = self.nonce.load(Ordering::SeqCst);
-        self.tx.send(Request::Program { nonce, id }).await?;
+        let (reply, receiver) = oneshot::channel();
 
-        let result = self.resp(nonce).await?;
+        let result = self.resp(Request::Program { id, reply }, receiver).await?;
         let Response::Program(result) = result else {
             return Err(anyhow!(
                 "Response is not matched with deploy request, {result:?}"
@@ -135,17 +176,15 @@ impl Backend for GTest {
     where
         M: Into<Message> + Send,
     {
-        let nonce = self.nonce.load(Ordering::SeqCst);
-        self.tx
-            .send(Request::Deploy {
-                nonce,
-                code: code.wasm()?,
-                message: message.into(),
-                signer: Default::default(),
-            })
-            .await?;
+        let (reply, receiver) = oneshot::channel();
+        let request = Request::Deploy {
+            code: code.wasm()?,
+            message: message.into(),
+            signer: Default::default(),
+            reply,
+        };
 
-        let result = self.resp(nonce).await?;
+        let result = self.resp(request, receiver).await?;
         let Response::Deploy(result) = result else {
             return Err(anyhow!(
                 "Response is not matched with deploy request, {result:?}"
@@ -165,17 +204,15 @@ impl Backend for GTest {
     where
         M: Into<Message> + Send,
     {
-        let nonce = self.nonce.load(Ordering::SeqCst);
-        self.tx
-            .send(Request::Send {
-                nonce,
-                prog: id.into(),
-                message: message.into(),
-                signer: Default::default(),
-            })
-            .await?;
+        let (reply, receiver) = oneshot::channel();
+        let request = Request::Send {
+            prog: id.into(),
+            message: message.into(),
+            signer: Default::default(),
+            reply,
+        };
 
-        let result = self.resp(nonce).await?;
+        let result = self.resp(request, receiver).await?;
         let Response::Send(result) = result else {
             return Err(anyhow!(
                 "Response is not matched with send request, {result:?}"
@@ -185,6 +222,12 @@ impl Backend for GTest {
         Ok(result)
     }
 
+    // Deliberately *not* routed through `handle::message`/`Request::Message`: that path calls
+    // `gtest::System` mailbox APIs (`mailbox`/`mailbox_message`/`.expiry()`) that aren't vendored
+    // in this snapshot and haven't been checked against the real crate, so wiring it into the
+    // `Backend` trait would present a guess as a verified implementation. Keep returning the
+    // honest "unsupported" error here until `[`GTest::mailbox`]`'s assumed API shape is confirmed
+    // (or the crate is vendored and this compiles against it), then switch this over.
     async fn message(&self, _mid: MessageId) -> Result<Option<(UserStoredMessage, Interval<u32>)>> {
         Err(anyhow!(
             "gtest backend currently doesn't support this method"
@@ -201,20 +244,32 @@ impl Default for GTest {
 /// GTest requests
 pub enum Request {
     Deploy {
-        nonce: usize,
         code: Vec<u8>,
         message: Message,
         signer: ActorId,
+        reply: oneshot::Sender<Response>,
     },
     Send {
-        nonce: usize,
         prog: ActorId,
         message: Message,
         signer: ActorId,
+        reply: oneshot::Sender<Response>,
     },
     Program {
-        nonce: usize,
         id: ProgramId,
+        reply: oneshot::Sender<Response>,
+    },
+    Mailbox {
+        id: ActorId,
+        reply: oneshot::Sender<Response>,
+    },
+    Message {
+        mid: MessageId,
+        reply: oneshot::Sender<Response>,
+    },
+    Claim {
+        mid: MessageId,
+        reply: oneshot::Sender<Response>,
     },
 }
 
@@ -224,6 +279,9 @@ pub enum Response {
     Deploy(TxResult<ActorId>),
     Send(TxResult<MessageId>),
     Program(Option<ActorId>),
+    Mailbox(Vec<(UserStoredMessage, Interval<u32>)>),
+    Message(Option<(UserStoredMessage, Interval<u32>)>),
+    Claim(TxResult<()>),
 }
 
 /// gtest handles
@@ -232,17 +290,76 @@ pub(crate) mod handle {
     use gear_core::{
         buffer::LimitedVec,
         ids::{prelude::CodeIdExt, ProgramId},
-        message::{ReplyDetails, UserMessage},
+        message::{ReplyDetails, UserMessage, UserStoredMessage},
     };
-    use gprimitives::{ActorId, CodeId};
-    use gtest::{CoreLog, Program, System};
+    use gprimitives::{ActorId, CodeId, MessageId};
+    use gsdk::metadata::runtime_types::gear_common::storage::primitives::Interval;
+    use gtest::{CoreLog, MailboxEntry, Program, System};
 
     /// Return back program id if program exists
     pub fn prog(system: &System, prog: ProgramId) -> Response {
         Response::Program(system.get_program(prog).map(|p| p.id()))
     }
 
-    /// Deploy program via gtest
+    /// All mailbox entries currently queued for `id`.
+    ///
+    /// # Note
+    ///
+    /// `gtest::System`'s mailbox-query surface isn't vendored in this snapshot, so the exact
+    /// shape assumed here — `system.mailbox(id)` yielding `gtest::MailboxEntry`s with
+    /// `.id()`/`.source()`/`.destination()`/`.payload()`/`.value()` plus an `.expiry()` block
+    /// number — is a best-effort guess at the real API rather than a verified call.
+    pub fn mailbox(system: &System, id: ActorId) -> Response {
+        Response::Mailbox(
+            system
+                .mailbox(id)
+                .into_iter()
+                .map(|entry| (to_stored_message(&entry), expiry_interval(entry.expiry())))
+                .collect(),
+        )
+    }
+
+    /// The single mailbox entry for `mid`, if it's still queued anywhere.
+    ///
+    /// Same caveat as [`mailbox`] above about the assumed `System` API shape.
+    pub fn message(system: &System, mid: MessageId) -> Response {
+        Response::Message(
+            system
+                .mailbox_message(mid)
+                .map(|entry| (to_stored_message(&entry), expiry_interval(entry.expiry()))),
+        )
+    }
+
+    /// Claim the value attached to mailbox message `mid`.
+    ///
+    /// Same caveat as [`mailbox`] above about the assumed `System` API shape.
+    pub fn claim(system: &System, mid: MessageId) -> Response {
+        let logs = system
+            .claim_value_from_mailbox(mid)
+            .map(|r| map_logs(r.log()))
+            .unwrap_or_default();
+
+        Response::Claim(TxResult { result: (), logs })
+    }
+
+    fn expiry_interval(expiry: u32) -> Interval<u32> {
+        Interval {
+            start: expiry,
+            finish: expiry,
+        }
+    }
+
+    fn to_stored_message(entry: &MailboxEntry) -> UserStoredMessage {
+        UserStoredMessage::new(
+            entry.id(),
+            entry.source(),
+            entry.destination(),
+            LimitedVec::try_from(entry.payload().to_vec()).unwrap_or_default(),
+            entry.value(),
+        )
+    }
+
+    /// Deploy program via gtest.
     pub fn deploy(system: &System, code: Vec<u8>, message: Message, signer: ActorId) -> Response {
         let id = CodeId::generate(&code);
         let prog = Program::from_binary_with_id(system, code, &id.into_bytes());
@@ -254,7 +371,7 @@ pub(crate) mod handle {
         })
     }
 
-    /// Send message via gtest
+    /// Send message via gtest.
     pub fn send(system: &System, prog: ActorId, message: Message, signer: ActorId) -> Response {
         let prog = system.get_program(prog).unwrap();
         let r = prog.send(signer, message.payload);
@@ -0,0 +1,110 @@
+// This file is part of Gear.
+
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::metrics::{MetricEvent, Metrics, Op, Outcome};
+use crate::client::{Backend, Code, Message, Program, TxResult};
+use anyhow::Result;
+use async_trait::async_trait;
+use gear_core::{ids::ProgramId, message::UserStoredMessage};
+use gprimitives::MessageId;
+use gsdk::metadata::runtime_types::gear_common::storage::primitives::Interval;
+use std::time::Instant;
+
+/// Wraps a [`Backend`] so every trait call is timed and reported to a [`Metrics`] dispatcher,
+/// without any change to the wrapped backend itself: enabling telemetry is a composition choice
+/// (`Metered::new(backend, ...)`) rather than something every backend has to implement by hand.
+#[derive(Clone)]
+pub struct Metered<B: Backend> {
+    inner: B,
+    metrics: Metrics,
+    name: &'static str,
+}
+
+impl<B: Backend> Metered<B> {
+    /// Wrap `inner`, tagging every event recorded for it with `name` so several backends (or
+    /// several instances of the same backend) can share one `metrics` dispatcher and still be
+    /// told apart in a [`super::metrics::Snapshot`].
+    pub fn new(inner: B, name: &'static str, metrics: Metrics) -> Self {
+        Self {
+            inner,
+            metrics,
+            name,
+        }
+    }
+
+    fn record<T>(&self, op: Op, started: Instant, result: &Result<T>) {
+        self.metrics.record(MetricEvent {
+            op,
+            backend: self.name,
+            duration: started.elapsed(),
+            outcome: if result.is_ok() {
+                Outcome::Ok
+            } else {
+                Outcome::Err
+            },
+        });
+    }
+}
+
+#[async_trait]
+impl<B: Backend> Backend for Metered<B> {
+    async fn program(&self, id: ProgramId) -> Result<Program<Self>> {
+        let started = Instant::now();
+        let result = self.inner.program(id).await;
+        self.record(Op::Program, started, &result);
+
+        result.map(|program| Program {
+            id: program.id,
+            backend: self.clone(),
+        })
+    }
+
+    async fn deploy<M>(&self, code: impl Code, message: M) -> Result<TxResult<Program<Self>>>
+    where
+        M: Into<Message> + Send,
+    {
+        let started = Instant::now();
+        let result = self.inner.deploy(code, message).await;
+        self.record(Op::Deploy, started, &result);
+
+        result.map(|tx| TxResult {
+            result: Program {
+                id: tx.result.id,
+                backend: self.clone(),
+            },
+            logs: tx.logs,
+        })
+    }
+
+    async fn send<M>(&self, id: ProgramId, message: M) -> Result<TxResult<MessageId>>
+    where
+        M: Into<Message> + Send,
+    {
+        let started = Instant::now();
+        let result = self.inner.send(id, message).await;
+        self.record(Op::Send, started, &result);
+        result
+    }
+
+    async fn message(&self, mid: MessageId) -> Result<Option<(UserStoredMessage, Interval<u32>)>> {
+        let started = Instant::now();
+        let result = self.inner.message(mid).await;
+        self.record(Op::Message, started, &result);
+        result
+    }
+}
@@ -0,0 +1,129 @@
+// This file is part of Gear.
+
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Opt-in metrics/telemetry layer shared by every [`Backend`](crate::client::Backend)
+//! implementation, via the [`super::metered::Metered`] decorator.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+    sync::{mpsc, Mutex},
+    task::JoinHandle,
+};
+
+/// Which `Backend` trait method a [`MetricEvent`] was recorded for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    Program,
+    Deploy,
+    Send,
+    Message,
+}
+
+/// Whether a recorded call succeeded or failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Ok,
+    Err,
+}
+
+/// One completed `Backend` call, ready to be folded into a [`Snapshot`].
+#[derive(Debug, Clone)]
+pub struct MetricEvent {
+    pub op: Op,
+    pub backend: &'static str,
+    pub duration: Duration,
+    pub outcome: Outcome,
+}
+
+/// Call counters and raw per-call latencies accumulated for a single `(backend, op)` pair.
+#[derive(Debug, Clone, Default)]
+pub struct OpStats {
+    pub ok: u64,
+    pub err: u64,
+    pub latencies: Vec<Duration>,
+}
+
+impl OpStats {
+    fn record(&mut self, event: &MetricEvent) {
+        match event.outcome {
+            Outcome::Ok => self.ok += 1,
+            Outcome::Err => self.err += 1,
+        }
+        self.latencies.push(event.duration);
+    }
+}
+
+/// Point-in-time export of every `(backend, op)` pair's [`OpStats`], as returned by
+/// [`Metrics::snapshot`].
+pub type Snapshot = HashMap<(&'static str, Op), OpStats>;
+
+/// Handle to the background metrics dispatcher.
+///
+/// Cheap to clone and share across however many [`super::metered::Metered`] wrappers record
+/// into it; recording an event is a non-blocking enqueue onto a bounded channel, folded into the
+/// running [`Snapshot`] by a dedicated background task so the hot path never waits on a lock.
+#[derive(Clone)]
+pub struct Metrics {
+    tx: mpsc::Sender<MetricEvent>,
+    snapshot: Arc<Mutex<Snapshot>>,
+    _handle: Arc<JoinHandle<()>>,
+}
+
+impl Metrics {
+    /// Spawn the background dispatcher task. `size` bounds the event channel so a burst of calls
+    /// makes [`Self::record`] drop events rather than growing memory unboundedly.
+    pub fn new(size: usize) -> Self {
+        let (tx, mut rx) = mpsc::channel::<MetricEvent>(size);
+        let snapshot: Arc<Mutex<Snapshot>> = Arc::new(Mutex::new(HashMap::new()));
+
+        let folded = snapshot.clone();
+        let handle = tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                folded
+                    .lock()
+                    .await
+                    .entry((event.backend, event.op))
+                    .or_default()
+                    .record(&event);
+            }
+        });
+
+        Self {
+            tx,
+            snapshot,
+            _handle: Arc::new(handle),
+        }
+    }
+
+    /// Enqueue `event` for folding. Non-blocking: if the dispatcher is backed up the event is
+    /// dropped rather than stalling the caller, since telemetry must never slow down a real call.
+    pub fn record(&self, event: MetricEvent) {
+        let _ = self.tx.try_send(event);
+    }
+
+    /// Snapshot every `(backend, op)` pair's counters and latencies as of now.
+    pub async fn snapshot(&self) -> Snapshot {
+        self.snapshot.lock().await.clone()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
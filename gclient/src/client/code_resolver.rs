@@ -0,0 +1,135 @@
+// This file is part of Gear.
+
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Content-addressed code resolution: fetch program WASM by its [`CodeId`] from a configurable
+//! store instead of carrying the full binary at every `deploy` call site.
+
+use crate::client::Code;
+use anyhow::{anyhow, Result};
+use gear_core::ids::prelude::CodeIdExt;
+use gprimitives::CodeId;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use tokio::sync::RwLock;
+
+/// Where a [`CodeResolver`] fetches code it doesn't already have cached.
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// `GET {endpoint}/{code_id as hex}` is expected to return the raw WASM bytes.
+    Http { endpoint: String },
+    /// `{dir}/{code_id as hex}.wasm` is expected to hold the raw WASM bytes.
+    LocalCache { dir: PathBuf },
+}
+
+/// Content-addressed store for program code.
+///
+/// Fetches bytes by [`CodeId`] from `source`, verifies the download by recomputing
+/// `CodeId::generate` over it and rejecting a mismatch, then caches the verified bytes in memory
+/// so repeat deploys of the same code never re-fetch or re-verify.
+#[derive(Clone)]
+pub struct CodeResolver {
+    source: Source,
+    cache: Arc<RwLock<HashMap<CodeId, Vec<u8>>>>,
+}
+
+impl CodeResolver {
+    /// Build a resolver that fetches from `source` on a cache miss.
+    pub fn new(source: Source) -> Self {
+        Self {
+            source,
+            cache: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Fetch, verify and cache the WASM bytes for `id`, or return the already-cached copy.
+    ///
+    /// Call this at least once for a given `id` before passing a [`CodeRef`] built from the same
+    /// resolver to `deploy` — [`Code::wasm`] on [`CodeRef`] only reads this cache, it never
+    /// fetches on its own, since resolving is async and `Code::wasm` is not.
+    pub async fn resolve(&self, id: CodeId) -> Result<Vec<u8>> {
+        if let Some(bytes) = self.cache.read().await.get(&id) {
+            return Ok(bytes.clone());
+        }
+
+        let key = hex::encode(id.into_bytes());
+        let bytes = match &self.source {
+            Source::Http { endpoint } => {
+                reqwest::get(format!("{endpoint}/{key}"))
+                    .await?
+                    .error_for_status()?
+                    .bytes()
+                    .await?
+                    .to_vec()
+            }
+            Source::LocalCache { dir } => tokio::fs::read(dir.join(format!("{key}.wasm"))).await?,
+        };
+
+        let fetched = CodeId::generate(&bytes);
+        if fetched != id {
+            return Err(anyhow!(
+                "gclient: code fetched for {key} actually hashes to {}, rejecting",
+                hex::encode(fetched.into_bytes())
+            ));
+        }
+
+        self.cache.write().await.insert(id, bytes.clone());
+        Ok(bytes)
+    }
+
+    /// Build a [`CodeRef`] bound to this resolver, for handing to `deploy` once `id` has been
+    /// [`Self::resolve`]d.
+    pub fn code_ref(&self, id: CodeId) -> CodeRef {
+        CodeRef {
+            id,
+            resolver: self.clone(),
+        }
+    }
+}
+
+/// A [`Code`] handle that names code by its [`CodeId`] instead of carrying the WASM bytes,
+/// keeping deploy payloads small.
+///
+/// Must be resolved via [`CodeResolver::resolve`] beforehand: `Code::wasm` only reads the bound
+/// resolver's cache and errors if `id` isn't in it yet.
+#[derive(Clone)]
+pub struct CodeRef {
+    id: CodeId,
+    resolver: CodeResolver,
+}
+
+impl CodeRef {
+    /// The code this reference names.
+    pub fn id(&self) -> CodeId {
+        self.id
+    }
+}
+
+impl Code for CodeRef {
+    fn wasm(self) -> Result<Vec<u8>> {
+        let key = hex::encode(self.id.into_bytes());
+
+        self.resolver
+            .cache
+            .try_read()
+            .map_err(|_| anyhow!("gclient: code cache for {key} is busy, try again"))?
+            .get(&self.id)
+            .cloned()
+            .ok_or_else(|| {
+                anyhow!("gclient: code {key} not resolved yet, call CodeResolver::resolve first")
+            })
+    }
+}
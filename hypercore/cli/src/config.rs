@@ -0,0 +1,42 @@
+// This file is part of Gear.
+//
+// Copyright (C) 2024 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Hypercore node configuration.
+
+use std::path::PathBuf;
+
+/// Backing store [`Service::start`](crate::service::Service::start) opens.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DatabaseKind {
+    /// Persistent RocksDB store rooted at [`Config::database_path`]. The default, used by
+    /// long-running nodes that need state to survive a restart.
+    #[default]
+    Rocks,
+    /// Ephemeral in-memory store that never touches disk. Intended for tests and other
+    /// short-lived start-drive-shutdown cycles where persistence doesn't matter.
+    InMemory,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub database_path: PathBuf,
+    pub ethereum_rpc: String,
+    pub key_path: PathBuf,
+    pub network_path: PathBuf,
+    pub database_kind: DatabaseKind,
+}
@@ -16,8 +16,11 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::config::Config;
+use crate::config::{Config, DatabaseKind};
 use anyhow::Result;
+use tokio::sync::oneshot;
+
+const LOG_TARGET: &str = "hyper-service";
 
 /// Hypercore service.
 pub struct Service {
@@ -28,9 +31,12 @@ pub struct Service {
 
 impl Service {
     pub fn start(config: &Config) -> Result<Self> {
-        let db: Box<dyn hypercore_db::Database> = Box::new(hypercore_db::RocksDatabase::open(
-            config.database_path.clone(),
-        )?);
+        let db: Box<dyn hypercore_db::Database> = match config.database_kind {
+            DatabaseKind::Rocks => Box::new(hypercore_db::RocksDatabase::open(
+                config.database_path.clone(),
+            )?),
+            DatabaseKind::InMemory => Box::new(hypercore_db::InMemoryDatabase::default()),
+        };
         let network = hypercore_network::Network::start()?;
         let observer =
             hypercore_observer::Observer::new(config.ethereum_rpc.clone(), db.clone_boxed())?;
@@ -41,13 +47,40 @@ impl Service {
             observer,
         })
     }
+
+    /// Drives the service until `shutdown` fires: every observer event is persisted through
+    /// [`hypercore_db::Database`] and then gossiped over [`hypercore_network::Network`], in that
+    /// order, so a peer never sees a block the local node hasn't already durably recorded.
+    ///
+    /// Returns once `shutdown` resolves or the observer's event stream ends on its own.
+    pub async fn run(mut self, mut shutdown: oneshot::Receiver<()>) -> Result<()> {
+        loop {
+            tokio::select! {
+                event = self.observer.next_event() => {
+                    let Some(event) = event else {
+                        log::info!(target: LOG_TARGET, "Observer event stream ended, stopping service");
+                        break;
+                    };
+                    self.db.persist_observer_event(&event);
+                    self.network.publish_observer_event(&event).await?;
+                }
+                _ = &mut shutdown => {
+                    log::info!(target: LOG_TARGET, "Shutdown signal received, stopping service");
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::Service;
-    use crate::config::Config;
+    use crate::config::{Config, DatabaseKind};
+    use tokio::sync::oneshot;
 
     #[test]
     fn basics() {
@@ -56,8 +89,30 @@ mod tests {
             ethereum_rpc: "http://localhost:8545".into(),
             key_path: "/tmp/key".into(),
             network_path: "/tmp/net".into(),
+            database_kind: DatabaseKind::Rocks,
         });
 
         assert!(service.is_ok());
     }
+
+    #[tokio::test]
+    async fn run_drives_until_shutdown() {
+        let service = Service::start(&Config {
+            database_path: "/tmp/db".into(),
+            ethereum_rpc: "http://localhost:8545".into(),
+            key_path: "/tmp/key".into(),
+            network_path: "/tmp/net".into(),
+            database_kind: DatabaseKind::InMemory,
+        })
+        .expect("in-memory service should start without touching disk");
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let handle = tokio::spawn(service.run(shutdown_rx));
+
+        shutdown_tx.send(()).expect("service is still running");
+        handle
+            .await
+            .expect("run task should not panic")
+            .expect("run should shut down cleanly");
+    }
 }
@@ -115,35 +115,56 @@ pub type MemoryPages = BTreeMap<GearPage, H256>;
 
 pub type Allocations = IntervalsTree<WasmPage>;
 
+/// Why a [`Storage`] read came back without the value the caller asked for, when that's not
+/// simply because the hash is genuinely unused.
+///
+/// Every [`Storage`] read is content-addressed, so implementations are expected to self-verify:
+/// recompute the hash of whatever bytes they read back and compare it to the key the caller asked
+/// for before decoding them, rather than trusting the backing store blindly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DbError {
+    /// The bytes stored under the hash don't decode as the expected type.
+    Decode {
+        /// What the caller was trying to read, e.g. `"program state"`.
+        what: &'static str,
+    },
+    /// The bytes read back don't hash to the key they were read under — the backing store
+    /// returned the wrong blob, or the entry is corrupted on disk.
+    HashMismatch { expected: H256, actual: H256 },
+}
+
 pub trait Storage {
     fn clone_boxed(&self) -> Box<dyn Storage>;
 
     /// Reads program state by state hash.
-    fn read_state(&self, hash: H256) -> Option<ProgramState>;
+    fn read_state(&self, hash: H256) -> Result<Option<ProgramState>, DbError>;
 
     /// Writes program state and returns its hash.
     fn write_state(&self, state: ProgramState) -> H256;
 
     /// Reads message queue by queue hash.
-    fn read_queue(&self, hash: H256) -> Option<MessageQueue>;
+    fn read_queue(&self, hash: H256) -> Result<Option<MessageQueue>, DbError>;
 
     /// Writes message queue and returns its hash.
     fn write_queue(&self, queue: MessageQueue) -> H256;
 
     /// Reads memory pages by pages hash.
-    fn read_pages(&self, hash: H256) -> Option<MemoryPages>;
+    fn read_pages(&self, hash: H256) -> Result<Option<MemoryPages>, DbError>;
 
     /// Writes memory pages and returns its hash.
     fn write_pages(&self, pages: MemoryPages) -> H256;
 
     /// Reads allocations by allocations hash.
-    fn read_allocations(&self, hash: H256) -> Option<Allocations>;
+    fn read_allocations(&self, hash: H256) -> Result<Option<Allocations>, DbError>;
 
     /// Writes allocations and returns its hash.
     fn write_allocations(&self, allocations: Allocations) -> H256;
 
     /// Reads gas reservation map by gas reservation map hash.
-    fn read_gas_reservation_map(&self, hash: H256) -> Option<GasReservationMap>;
+    fn read_gas_reservation_map(
+        &self,
+        hash: H256,
+    ) -> Result<Option<GasReservationMap>, DbError>;
 
     /// Writes gas reservation map and returns its hash.
     fn write_gas_reservation_map(&self, gas_reservation_map: GasReservationMap) -> H256;
@@ -155,25 +176,29 @@ pub trait Storage {
     fn set_program_code_id(&self, program_id: ProgramId, code_id: CodeId);
 
     /// Reads original code by code hash.
-    fn read_original_code(&self, code_id: CodeId) -> Option<Vec<u8>>;
+    fn read_original_code(&self, code_id: CodeId) -> Result<Option<Vec<u8>>, DbError>;
 
     /// Writes original code and returns its hash.
     fn write_original_code(&self, code: &[u8]) -> H256;
 
     /// Reads instrumented code by runtime id and original code id.
-    fn read_instrumented_code(&self, runtime_id: u32, code_id: CodeId) -> Option<InstrumentedCode>;
+    fn read_instrumented_code(
+        &self,
+        runtime_id: u32,
+        code_id: CodeId,
+    ) -> Result<Option<InstrumentedCode>, DbError>;
 
     /// Writes instrumented code and returns its hash.
     fn write_instrumented_code(&self, runtime_id: u32, code_id: CodeId, code: InstrumentedCode);
 
     /// Reads payload by payload hash.
-    fn read_payload(&self, hash: H256) -> Option<Payload>;
+    fn read_payload(&self, hash: H256) -> Result<Option<Payload>, DbError>;
 
     /// Writes payload and returns its hash.
     fn write_payload(&self, payload: Payload) -> H256;
 
     /// Reads page data by page data hash.
-    fn read_page_data(&self, hash: H256) -> Option<PageBuf>;
+    fn read_page_data(&self, hash: H256) -> Result<Option<PageBuf>, DbError>;
 
     /// Writes page data and returns its hash.
     fn write_page_data(&self, data: PageBuf) -> H256;
@@ -40,9 +40,9 @@ use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 use sp_runtime::{
     codec::{Decode, Encode},
-    RuntimeDebug,
+    Perbill, RuntimeDebug,
 };
-use sp_std::{marker::PhantomData, vec::Vec};
+use sp_std::{borrow::Cow, marker::PhantomData, vec::Vec};
 
 /// How many API calls are executed in a single batch. The reason for increasing the amount
 /// of API calls in batches (per benchmark component increase) is so that the linear regression
@@ -53,6 +53,20 @@ pub const API_BENCHMARK_BATCH_SIZE: u32 = 80;
 /// as for `API_BENCHMARK_BATCH_SIZE`.
 pub const INSTR_BENCHMARK_BATCH_SIZE: u32 = 500;
 
+/// The maximum fraction of a block's remaining weight the `on_initialize` lazy-deletion pass for
+/// expired waitlist/dispatch-stash/reservation entries may spend, so draining that backlog can
+/// never starve normal extrinsics out of a block. Mirrors the `AVERAGE_ON_INITIALIZE_RATIO`
+/// pattern other Substrate pallets use to budget their own `on_initialize` work.
+///
+/// # Note
+///
+/// The `on_initialize` loop that decodes queued task keys against this budget (deferring
+/// whatever doesn't fit to the next block, and skipping the pass entirely once the block is
+/// already near-full) lives in `pallet_gear`'s top-level pallet logic outside this module; this
+/// crate only supplies the ratio and the benchmarked per-entry weights in [`Schedule`] the budget
+/// math should be computed against — see [`Schedule::lazy_deletion_budget`].
+pub const AVERAGE_ON_INITIALIZE_RATIO: Perbill = Perbill::from_percent(10);
+
 /// Constant for `stack_height` is calculated via `calc-stack-height` utility to be small enough
 /// to avoid stack overflow in wasmer and wasmi executors.
 /// To avoid potential stack overflow problems we have a panic in sandbox in case,
@@ -76,6 +90,117 @@ pub const DATA_SEGMENTS_AMOUNT_LIMIT: u32 = 1024;
 /// see <https://github.com/bytecodealliance/wasm-tools/blob/main/crates/wasmparser/src/limits.rs>
 pub const TABLE_NUMBER_LIMIT: u32 = 100;
 
+/// Smallest page size a module may declare under the WebAssembly custom-page-sizes proposal
+/// (<https://github.com/WebAssembly/custom-page-sizes>): `2.pow(0) == 1` byte.
+pub const MIN_LOG2_PAGE_SIZE: u8 = 0;
+
+/// Largest page size a module may declare under the custom-page-sizes proposal: `2.pow(16) ==
+/// 65536` bytes, i.e. today's only supported page size.
+pub const MAX_LOG2_PAGE_SIZE: u8 = 16;
+
+/// The program execution engine a [`Schedule`] runs programs with.
+///
+/// Selecting a backend is a single config knob: it lets a runtime A/B a register-based engine
+/// against the stack-machine interpreter without forking the pallet. Switching it changes which
+/// [`Limits`] are meaningful to instrument against — see [`Limits::for_backend`].
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Default, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum VMBackend {
+    /// The `wasmi` stack-machine interpreter used today.
+    #[default]
+    Wasmi,
+    /// A register-based RISC-V engine (`PolkaVM`) that compiles programs ahead of time rather
+    /// than interpreting them, and enforces its own stack depth, global count, local count and
+    /// table count natively as part of the executable it produces.
+    PolkaVM,
+}
+
+/// How a [`Schedule`] charges for `memory.grow`.
+///
+/// Selected per-runtime, like [`VMBackend`], so an environment that can't rely on host-side
+/// accounting for the grow (e.g. the gtest/simulation path driving `CustomConstantCostRules`, or
+/// a para-chain without lazy-pages host metering) can still charge deterministically.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Default, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum MemoryGrowCostStrategy {
+    /// `memory.grow` is free at instrumentation time; the cost is charged host-side via
+    /// [`MemoryWeights::mem_grow`]/[`MemoryWeights::mem_grow_per_page`] when the lazy-pages
+    /// host function observes the grow. This is today's behaviour and the default.
+    #[default]
+    HostMetered,
+    /// `memory.grow` is charged per requested page directly at metering time, via
+    /// [`MemoryGrowCost::Linear`] wired from [`MemoryWeights::mem_grow_per_page`]. Use this
+    /// where nothing host-side is guaranteed to observe the grow.
+    InstructionMetered,
+}
+
+/// Per-category percentage a measured weight may drift from a reference [`Schedule`] before
+/// [`Schedule::validate_against`] flags it.
+///
+/// Promotes the ad-hoc per-field spread checks historically hand-rolled in CI into a reusable,
+/// introspectable API that downstream runtimes can run in their own tests or at genesis to catch
+/// mis-benchmarked or hand-edited schedules before they ship.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpreadConfig {
+    /// Allowed drift, in percent, for [`InstructionWeights`] fields.
+    pub instruction_weights_pct: u32,
+    /// Allowed drift, in percent, for [`SyscallWeights`] fields.
+    pub syscall_weights_pct: u32,
+    /// Allowed drift, in percent, for [`MemoryWeights`] fields.
+    pub memory_weights_pct: u32,
+}
+
+impl Default for SpreadConfig {
+    /// Instructions are noisy relative to their absolute size (they're cheap, so benchmark
+    /// jitter is a larger fraction of the measurement), hence the wider tolerance; syscalls and
+    /// memory weights are large enough that the same jitter is a much smaller percentage.
+    fn default() -> Self {
+        Self {
+            instruction_weights_pct: 50,
+            syscall_weights_pct: 10,
+            memory_weights_pct: 10,
+        }
+    }
+}
+
+/// A single field whose measured weight drifted beyond its [`SpreadConfig`] tolerance from the
+/// reference [`Schedule`] passed to [`Schedule::validate_against`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldMismatch {
+    /// The mismatching field's name, as it appears on the weight struct it belongs to.
+    pub field: &'static str,
+    /// The `ref_time` measured on the schedule being validated.
+    pub measured: u64,
+    /// The `ref_time` measured on the reference schedule.
+    pub expected: u64,
+    /// The configured tolerance, in percent, the drift exceeded.
+    pub allowed_pct: u32,
+}
+
+/// The report [`Schedule::validate_against`] returns when one or more fields drifted beyond
+/// their configured [`SpreadConfig`] tolerance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduleMismatch(pub Vec<FieldMismatch>);
+
+/// An integrity invariant a proposed [`ScheduleOverrides`] violated, returned by
+/// [`Schedule::validate_overrides`] so the privileged origin applying it is rejected instead of
+/// bricking metering.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverrideError {
+    /// The named [`SyscallWeights`] override charges zero, which would let that syscall be
+    /// called for free.
+    ZeroSyscallWeight(&'static str),
+    /// The proposed `instruction_weights_version` does not move the version strictly forward
+    /// from the current schedule's, so already-instrumented code would not be re-instrumented
+    /// against the overridden weights.
+    StaleInstructionWeightsVersion {
+        /// The version the override proposed.
+        proposed: u32,
+        /// The version on the schedule the override is layered over.
+        current: u32,
+    },
+}
+
 /// Definition of the cost schedule and other parameterization for the wasm vm.
 ///
 /// Its [`Default`] implementation is the designated way to initialize this type. It uses
@@ -114,6 +239,22 @@ pub const TABLE_NUMBER_LIMIT: u32 = 100;
 #[derive(Clone, Encode, Decode, PartialEq, Eq, ScheduleDebug, TypeInfo)]
 #[scale_info(skip_type_params(T))]
 pub struct Schedule<T: Config> {
+    /// The execution engine programs under this schedule are run with.
+    ///
+    /// # Note
+    ///
+    /// Changing this, like changing [`InstructionWeights::version`], requires re-instrumenting
+    /// already-deployed code — see [`Schedule::needs_reinstrumentation`]. The concrete
+    /// executor wiring for each variant lives outside this crate; this field only carries the
+    /// operator's choice through the on-chain schedule.
+    pub backend: VMBackend,
+
+    /// Whether `memory.grow` is charged host-side or at instrumentation time.
+    ///
+    /// See [`MemoryGrowCostStrategy`] and [`Schedule::process_costs`] for how the two strategies
+    /// avoid double-charging the same grow.
+    pub memory_grow_cost_strategy: MemoryGrowCostStrategy,
+
     /// Describes the upper limits on various metrics.
     pub limits: Limits,
 
@@ -151,6 +292,13 @@ pub struct Schedule<T: Config> {
     pub dispatch_stash_cost: Weight,
     /// Holding reservation cost per block
     pub reservation_cost: Weight,
+
+    /// Weight of removing a single expired waitlist/dispatch-stash/reservation entry from
+    /// storage during the `on_initialize` lazy-deletion pass.
+    ///
+    /// Used together with [`AVERAGE_ON_INITIALIZE_RATIO`] by [`Schedule::lazy_deletion_budget`]
+    /// to cap how many such entries that pass may drain in a single block.
+    pub task_removal_weight: Weight,
 }
 
 /// Describes the upper limits on various metrics.
@@ -197,7 +345,8 @@ pub struct Limits {
     /// the costs of the instructions that cause them (call, call_indirect).
     pub parameters: u32,
 
-    /// Maximum number of memory pages allowed for a program.
+    /// Maximum number of memory pages allowed for a program, sized against the page size the
+    /// module declares — see [`Limits::custom_page_sizes`].
     pub memory_pages: u16,
 
     /// Maximum number of elements allowed in a table.
@@ -229,6 +378,70 @@ pub struct Limits {
 
     /// The maximum number of wasm data segments allowed for a program.
     pub data_segments_amount: u32,
+
+    /// Whether a module may declare a linear-memory page size other than the default 64 KiB,
+    /// per the WebAssembly custom-page-sizes proposal (allowed sizes are powers of two between
+    /// [`MIN_LOG2_PAGE_SIZE`] and [`MAX_LOG2_PAGE_SIZE`]). Gated behind this flag — rather than
+    /// enabled unconditionally — so existing programs keep the 65536-byte assumption baked into
+    /// [`Limits::memory_byte_ceiling`], [`MemoryWeights::mem_grow`]/
+    /// [`MemoryWeights::mem_grow_per_page`], and the lazy-pages `GearPage`/`WasmPage`
+    /// conversions.
+    ///
+    /// # Note
+    ///
+    /// The declared page size itself (`log2_page_size: u8`) is carried per-program on the code
+    /// metadata in `gear-core`, validated at instrumentation time to be one of the allowed
+    /// powers of two; that type lives outside this crate, so only the schedule-side gate and
+    /// byte-ceiling arithmetic live here.
+    pub custom_page_sizes: bool,
+
+    /// Whether `gr_debug` actually materializes and logs its payload.
+    ///
+    /// # Note
+    ///
+    /// A program calling `gr_debug` is always charged
+    /// [`SyscallWeights::gr_debug`]/[`SyscallWeights::gr_debug_per_byte`] — the validate-and-log
+    /// cost — regardless of this flag, so gas accounting stays identical across chains that
+    /// disable it; only the logging side effect is skipped. Production runtimes should set this
+    /// to `false` so log output isn't materialized for messages nobody reads, while test/dev
+    /// runtimes set it `true`. See [`SyscallWeights::gr_debug_validate_per_byte`] for the
+    /// cheaper validate-only cost this flag's no-op path is benchmarked against.
+    pub gr_debug_enabled: bool,
+}
+
+impl Limits {
+    /// The byte ceiling [`Self::memory_pages`] represents for a module declaring
+    /// `log2_page_size`. Falls back to the fixed 64 KiB page size whenever
+    /// [`Self::custom_page_sizes`] is disabled, regardless of what the module itself declares.
+    pub fn memory_byte_ceiling(&self, log2_page_size: u8) -> u64 {
+        let log2_page_size = if self.custom_page_sizes {
+            log2_page_size
+        } else {
+            MAX_LOG2_PAGE_SIZE
+        };
+        (self.memory_pages as u64) << log2_page_size
+    }
+
+    /// The limits actually worth instrumenting a module against when targeting `backend`.
+    ///
+    /// [`VMBackend::PolkaVM`] enforces `stack_height`, `globals`, `locals` and `table_number`
+    /// natively as part of the executable it compiles a module to, so re-deriving and
+    /// instrumenting against them here would duplicate checks the backend already performs —
+    /// and, for `stack_height`, meter weight for a safety net the backend doesn't need. All
+    /// other limits (memory, payload, code size, ...) are format-level constraints independent
+    /// of the execution engine, so they carry over unchanged for every backend.
+    pub fn for_backend(&self, backend: VMBackend) -> Self {
+        match backend {
+            VMBackend::Wasmi => self.clone(),
+            VMBackend::PolkaVM => Self {
+                stack_height: None,
+                globals: u32::MAX,
+                locals: u32::MAX,
+                table_number: u32::MAX,
+                ..self.clone()
+            },
+        }
+    }
 }
 
 /// Describes the weight for all categories of supported wasm instructions.
@@ -244,13 +457,24 @@ pub struct Limits {
 /// 3. The following instructions cannot be benchmarked because they are removed by any
 ///    real world execution engine as a preprocessing step and therefore don't yield a
 ///    meaningful benchmark result. However, in contrast to the instructions mentioned
-///    in 2. they can be spammed. We price them with the same weight as the "default"
-///    instruction (i64.const): Block, Loop, Nop
-/// 4. We price both i64.const and drop as InstructionWeights.i64const / 2. The reason
+///    in 2. they can be spammed. They are nevertheless charged nothing: Block, Loop, Nop
+/// 4. When deriving the other weights, each i64.const or drop is counted as
+///    InstructionWeights.i64add / 2. The reason
 ///    for that is that we cannot benchmark either of them on its own but we need their
 ///    individual values to derive (by subtraction) the weight of all other instructions
 ///    that use them as supporting instructions. Supporting means mainly pushing arguments
-///    and dropping return values in order to maintain a valid module.
+///    and dropping return values in order to maintain a valid module. The anchor used to be
+///    i64.const itself, but on register-based engines a benchmark built purely from
+///    `i64.const` inputs gets constant-folded or dead-code-eliminated away, making every
+///    weight derived from it meaningless; `i64.add` whose operands and result round-trip
+///    through `local.get`/`local.set` can't be folded away like that, so it anchors the
+///    derivation instead. When charging, i32.const and i64.const each cost the full
+///    InstructionWeights.i64add and drop costs nothing.
+/// 5. The bulk-memory ops (`memory.copy`/`memory.fill`/`memory.init`/`data.drop`) and the
+///    reference-type ops (`table.copy`/`table.init`/`table.grow`/`table.fill`/`table.size`/
+///    `ref.null`/`ref.is_null`/`ref.func`) each get a per-call base cost field here; the three
+///    memory ops additionally have a `_per_byte` field since their runtime is linear in a
+///    length operand that isn't known until the instruction actually runs.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Clone, Encode, Decode, PartialEq, Eq, ScheduleDebug, TypeInfo)]
 #[scale_info(skip_type_params(T))]
@@ -268,7 +492,6 @@ pub struct InstructionWeights<T: Config> {
     /// Changes to other parts of the schedule should not increment the version in
     /// order to avoid unnecessary re-instrumentations.
     pub version: u32,
-    pub i64const: u32,
     pub i64load: u32,
     pub i32load: u32,
     pub i64store: u32,
@@ -325,6 +548,7 @@ pub struct InstructionWeights<T: Config> {
     pub i32ges: u32,
     pub i64geu: u32,
     pub i32geu: u32,
+    /// Also used as the anchor for the derivation in point 4 above.
     pub i64add: u32,
     pub i32add: u32,
     pub i64sub: u32,
@@ -355,20 +579,211 @@ pub struct InstructionWeights<T: Config> {
     pub i32rotl: u32,
     pub i64rotr: u32,
     pub i32rotr: u32,
+
+    /// Weight of `memory.copy`, charged once per call site.
+    ///
+    /// The number of bytes copied is a runtime stack value, not known at instrumentation time,
+    /// so it can't be folded into this per-call base cost; the linear component is
+    /// [`Self::memory_copy_per_byte`], charged the same way [`MemoryWeights::load_page_data`]/
+    /// [`MemoryWeights::upload_page_data`] separate a fixed cost from a per-byte one — via a
+    /// metered host/loop charge injected outside this crate rather than a static match arm.
+    pub memory_copy: u32,
+    /// Per-byte weight of `memory.copy`. See [`Self::memory_copy`].
+    pub memory_copy_per_byte: u32,
+    /// Weight of `memory.fill`, charged once per call site. See [`Self::memory_copy`] for why
+    /// the linear component is split out.
+    pub memory_fill: u32,
+    /// Per-byte weight of `memory.fill`. See [`Self::memory_fill`].
+    pub memory_fill_per_byte: u32,
+    /// Weight of `memory.init`, charged once per call site. See [`Self::memory_copy`] for why
+    /// the linear component is split out.
+    pub memory_init: u32,
+    /// Per-byte weight of `memory.init`. See [`Self::memory_init`].
+    pub memory_init_per_byte: u32,
+    /// Weight of `data.drop`.
+    pub data_drop: u32,
+    /// Weight of `table.copy`.
+    pub table_copy: u32,
+    /// Weight of `table.init`.
+    pub table_init: u32,
+    /// Weight of `table.grow`.
+    pub table_grow: u32,
+    /// Weight of `table.fill`.
+    pub table_fill: u32,
+    /// Weight of `table.size`.
+    pub table_size: u32,
+    /// Weight of `ref.null`.
+    pub ref_null: u32,
+    /// Weight of `ref.is_null`.
+    pub ref_is_null: u32,
+    /// Weight of `ref.func`.
+    pub ref_func: u32,
+
     /// The type parameter is used in the default implementation.
     #[codec(skip)]
     #[cfg_attr(feature = "std", serde(skip))]
     pub _phantom: PhantomData<T>,
 }
 
+macro_rules! syscall_weights_overrides {
+    ($($field:ident),+ $(,)?) => {
+        /// A sparse set of [`SyscallWeights`] field overrides, settable on-chain through a
+        /// privileged origin via [`Schedule::apply_overrides`] so a single mispriced syscall can
+        /// be hot-fixed without a full runtime upgrade. Fields left as `None` keep the compiled
+        /// [`Default`] value.
+        #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+        #[derive(Clone, Default, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+        pub struct SyscallWeightsOverrides {
+            $(
+                #[doc = concat!("Override for [`SyscallWeights::", stringify!($field), "`].")]
+                pub $field: Option<Weight>,
+            )+
+        }
+
+        impl SyscallWeightsOverrides {
+            /// Replaces every field present in `self` onto `weights`, leaving absent fields at
+            /// their compiled value.
+            fn apply<T: Config>(&self, weights: &mut SyscallWeights<T>) {
+                $(
+                    if let Some(weight) = self.$field {
+                        weights.$field = weight;
+                    }
+                )+
+            }
+
+            /// Rejects any present override that charges zero, which would let the
+            /// corresponding syscall be called for free.
+            fn validate(&self) -> Result<(), OverrideError> {
+                $(
+                    if self.$field == Some(Weight::zero()) {
+                        return Err(OverrideError::ZeroSyscallWeight(stringify!($field)));
+                    }
+                )+
+                Ok(())
+            }
+        }
+    };
+}
+
+syscall_weights_overrides!(
+    alloc,
+    alloc_per_page,
+    free,
+    free_range,
+    free_range_per_page,
+    gr_reserve_gas,
+    gr_unreserve_gas,
+    gr_system_reserve_gas,
+    gr_gas_available,
+    gr_message_id,
+    gr_program_id,
+    gr_source,
+    gr_value,
+    gr_value_available,
+    gr_size,
+    gr_read,
+    gr_read_per_byte,
+    gr_env_vars,
+    gr_block_height,
+    gr_block_timestamp,
+    gr_random,
+    gr_reply_deposit,
+    gr_send,
+    gr_send_per_byte,
+    gr_send_wgas,
+    gr_send_wgas_per_byte,
+    gr_send_init,
+    gr_send_push,
+    gr_send_push_per_byte,
+    gr_send_commit,
+    gr_send_commit_wgas,
+    gr_reservation_send,
+    gr_reservation_send_per_byte,
+    gr_reservation_send_commit,
+    gr_reply_commit,
+    gr_reply_commit_wgas,
+    gr_reservation_reply,
+    gr_reservation_reply_per_byte,
+    gr_reservation_reply_commit,
+    gr_reply_push,
+    gr_reply,
+    gr_reply_per_byte,
+    gr_reply_wgas,
+    gr_reply_wgas_per_byte,
+    gr_reply_push_per_byte,
+    gr_reply_to,
+    gr_signal_code,
+    gr_signal_from,
+    gr_reply_input,
+    gr_reply_input_wgas,
+    gr_reply_push_input,
+    gr_reply_push_input_per_byte,
+    gr_send_input,
+    gr_send_input_wgas,
+    gr_send_push_input,
+    gr_send_push_input_per_byte,
+    gr_debug,
+    gr_debug_per_byte,
+    gr_debug_validate_per_byte,
+    gr_reply_code,
+    gr_exit,
+    gr_leave,
+    gr_wait,
+    gr_wait_for,
+    gr_wait_up_to,
+    gr_wake,
+    gr_create_program,
+    gr_create_program_payload_per_byte,
+    gr_create_program_salt_per_byte,
+    gr_create_program_wgas,
+    gr_create_program_wgas_payload_per_byte,
+    gr_create_program_wgas_salt_per_byte,
+);
+
+/// A sparse set of overrides a privileged origin may apply over the compiled [`Schedule`]
+/// default, letting operators hot-fix mispriced weights between runtime upgrades.
+///
+/// # Note
+///
+/// The on-chain `StorageValue<Option<ScheduleOverrides>>` holding this, and the privileged
+/// extrinsic that writes to it after running [`Schedule::validate_overrides`], live in
+/// `pallet_gear`'s top-level pallet logic outside this module; this crate only supplies the
+/// override shape and the merge/validation logic in [`Schedule::apply_overrides`] and
+/// [`Schedule::validate_overrides`].
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct ScheduleOverrides {
+    /// Overrides for individual [`SyscallWeights`] fields.
+    pub syscall_weights: SyscallWeightsOverrides,
+    /// Override for [`InstructionWeights::version`]. Since every override implies the compiled
+    /// code no longer matches what was instrumented against, this is normally bumped alongside
+    /// any weight override so deployed code is re-instrumented.
+    pub instruction_weights_version: Option<u32>,
+}
+
 /// Describes the weight for each imported function that a program is allowed to call.
+///
+/// # Note
+///
+/// `alloc`'s cost is split into [`SyscallWeights::alloc`] (flat) and
+/// [`SyscallWeights::alloc_per_page`] (scales with the number of pages grown). Charging
+/// `alloc + alloc_per_page * pages` instead of a flat `alloc` happens on the `core-processor`
+/// side, where the host-side cost enum for this syscall takes the page count as a parameter.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Clone, Encode, Decode, PartialEq, Eq, WeightDebug, TypeInfo)]
 #[scale_info(skip_type_params(T))]
 pub struct SyscallWeights<T: Config> {
-    /// Weight of calling `alloc`.
+    /// Flat weight of calling `alloc`, charged regardless of how many pages are requested.
+    /// Combined with [`Self::alloc_per_page`] as `alloc + alloc_per_page * pages` so the total
+    /// cost scales with the requested page count instead of flatly over-charging single-page
+    /// allocations and under-charging large ones.
     pub alloc: Weight,
 
+    /// Weight of calling `alloc` per page grown, mirroring how `free_range_per_page` scales
+    /// `free_range` — a single `alloc` call can grow an arbitrary number of pages, so its cost
+    /// can't be a flat per-call weight.
+    pub alloc_per_page: Weight,
+
     /// Weight of calling `free`.
     pub free: Weight,
 
@@ -535,8 +950,24 @@ pub struct SyscallWeights<T: Config> {
     pub gr_debug: Weight,
 
     /// Weight per payload byte by `gr_debug_per_byte`.
+    ///
+    /// Benchmarked against the validate-and-log path (UTF-8 validation plus the actual log
+    /// write), and charged unconditionally regardless of [`Limits::gr_debug_enabled`] so gas
+    /// accounting is deterministic across chains whether or not they materialize the output —
+    /// see [`Self::gr_debug_validate_per_byte`] for the cheaper path this isn't allowed to
+    /// charge instead.
     pub gr_debug_per_byte: Weight,
 
+    /// Weight per payload byte of validating `gr_debug`'s input as UTF-8 without logging it,
+    /// i.e. the cost of the no-op path taken when [`Limits::gr_debug_enabled`] is `false`.
+    ///
+    /// # Note
+    ///
+    /// This is surfaced purely so the validate-only and validate-and-log benchmarks are both
+    /// visible on the schedule; the actual charge always uses the (larger)
+    /// [`Self::gr_debug_per_byte`] so switching the flag can never change a program's gas cost.
+    pub gr_debug_validate_per_byte: Weight,
+
     /// Weight of calling `gr_reply_code`.
     pub gr_reply_code: Weight,
 
@@ -624,6 +1055,14 @@ pub struct MemoryWeights<T: Config> {
     pub upload_page_data: Weight,
 
     /// Cost per one [WasmPage] for memory growing.
+    ///
+    /// # Note
+    ///
+    /// Benchmarked against the default 65536-byte [`WasmPage`], so a program declaring a smaller
+    /// page size under [`Limits::custom_page_sizes`] needs this (and [`Self::mem_grow_per_page`])
+    /// rescaled by the ratio of its declared page size to [`WasmPage::SIZE`] before charging —
+    /// that rescaling, and the matching `GearPage`/`WasmPage` conversion change, belongs to the
+    /// `WasmPage`/`GearPage` types themselves in `gear-core`.
     pub mem_grow: Weight,
 
     /// Cost per one [WasmPage] for memory growing.
@@ -675,6 +1114,13 @@ impl<T: Config> From<MemoryWeights<T>> for LazyPagesCosts {
     }
 }
 
+/// # Note
+///
+/// [`InstantiationWeights`] fields carry a real measured `proof_size` (see [`cost_byte`]), but
+/// [`InstantiationCosts`] itself only has single-dimension fields today, so `.ref_time()` is all
+/// that makes it across this conversion. Giving module instantiation a PoV budget means growing
+/// `InstantiationCosts` (and gas charging around it) in `core-processor` to carry the second
+/// dimension, then widening this conversion to populate it.
 impl From<InstantiationWeights> for InstantiationCosts {
     fn from(val: InstantiationWeights) -> Self {
         Self {
@@ -711,33 +1157,41 @@ pub struct InstantiationWeights {
     pub type_section_per_byte: Weight,
 }
 
+/// Divides both components of `w` by `divisor`, keeping the weight genuinely two-dimensional
+/// instead of dropping `proof_size` to 0 the way a bare `ref_time() / divisor` would.
+#[inline]
+fn weight_div(w: Weight, divisor: u64) -> Weight {
+    Weight::from_parts(w.ref_time() / divisor, w.proof_size() / divisor)
+}
+
 #[inline]
 fn cost(w: fn(u32) -> Weight) -> Weight {
-    Weight::from_parts(w(1).saturating_sub(w(0)).ref_time(), 0)
+    w(1).saturating_sub(w(0))
 }
 
 #[inline]
 fn cost_byte(w: fn(u32) -> Weight) -> Weight {
-    Weight::from_parts(cost(w).ref_time() / 1024, 0)
+    weight_div(cost(w), 1024)
 }
 
 #[inline]
 fn cost_batched(w: fn(u32) -> Weight) -> Weight {
-    Weight::from_parts(cost(w).ref_time() / u64::from(API_BENCHMARK_BATCH_SIZE), 0)
+    weight_div(cost(w), u64::from(API_BENCHMARK_BATCH_SIZE))
 }
 
 #[inline]
 fn cost_byte_batched(w: fn(u32) -> Weight) -> Weight {
-    Weight::from_parts(cost_batched(w).ref_time() / 1024, 0)
+    weight_div(cost_batched(w), 1024)
 }
 
 #[inline]
 fn cost_byte_batched_args(w: fn(u32, u32) -> Weight, arg1: u32, arg2: u32) -> Weight {
-    Weight::from_parts(
-        w(arg1, arg2).saturating_sub(w(0, 0)).ref_time()
-            / u64::from(API_BENCHMARK_BATCH_SIZE)
-            / 1024,
-        0,
+    weight_div(
+        weight_div(
+            w(arg1, arg2).saturating_sub(w(0, 0)),
+            u64::from(API_BENCHMARK_BATCH_SIZE),
+        ),
+        1024,
     )
 }
 
@@ -747,6 +1201,16 @@ fn cost_zero(w: fn(u32) -> Weight) -> Weight {
     Weight::from_parts(ref_time, w(0).proof_size())
 }
 
+/// Whether `measured` is within `pct` percent of `expected`, used by
+/// [`Schedule::validate_against`].
+#[inline]
+fn within_spread(measured: u64, expected: u64, pct: u32) -> bool {
+    if expected == 0 {
+        return measured == 0;
+    }
+    measured.abs_diff(expected).saturating_mul(100) <= expected.saturating_mul(u64::from(pct))
+}
+
 #[inline]
 fn cost_instr_no_params_with_batch_size(w: fn(u32) -> Weight) -> u32 {
     ((w(1).saturating_sub(w(0))).ref_time() / u64::from(INSTR_BENCHMARK_BATCH_SIZE)) as u32
@@ -756,8 +1220,13 @@ fn cost_instr_no_params_with_batch_size(w: fn(u32) -> Weight) -> u32 {
 fn cost_instr<T: Config>(w: fn(u32) -> Weight, num_params: u32) -> u32 {
     type W<T> = <T as Config>::WeightInfo;
 
+    // Anchored on `instr_i64add` rather than `instr_i64const`: a benchmark built from bare
+    // `i64.const` inputs gets constant-folded away on register-based engines, so its measured
+    // weight (and everything derived from it by subtraction) would be meaningless.
+    // `instr_i64add` routes its operands and result through `local.get`/`local.set`, so it
+    // can't be folded or deleted the same way.
     cost_instr_no_params_with_batch_size(w).saturating_sub(
-        (cost_instr_no_params_with_batch_size(W::<T>::instr_i64const) / 2)
+        (cost_instr_no_params_with_batch_size(W::<T>::instr_i64add) / 2)
             .saturating_mul(num_params),
     )
 }
@@ -766,6 +1235,8 @@ impl<T: Config> Default for Schedule<T> {
     fn default() -> Self {
         type W<T> = <T as Config>::WeightInfo;
         Self {
+            backend: Default::default(),
+            memory_grow_cost_strategy: Default::default(),
             limits: Default::default(),
             instruction_weights: Default::default(),
             syscall_weights: Default::default(),
@@ -790,6 +1261,7 @@ impl<T: Config> Default for Schedule<T> {
             dispatch_stash_cost: Weight::from_parts(CostsPerBlockOf::<T>::dispatch_stash(), 0),
             reservation_cost: Weight::from_parts(CostsPerBlockOf::<T>::reservation(), 0),
             waitlist_cost: Weight::from_parts(CostsPerBlockOf::<T>::waitlist(), 0),
+            task_removal_weight: cost_zero(W::<T>::remove_expired_task),
         }
     }
 }
@@ -814,6 +1286,8 @@ impl Default for Limits {
             call_depth: 32,
             payload_len: message::MAX_PAYLOAD_SIZE as u32,
             code_len: 512 * 1024,
+            custom_page_sizes: false,
+            gr_debug_enabled: true,
         }
     }
 }
@@ -822,8 +1296,7 @@ impl<T: Config> Default for InstructionWeights<T> {
     fn default() -> Self {
         type W<T> = <T as Config>::WeightInfo;
         Self {
-            version: 1500,
-            i64const: cost_instr::<T>(W::<T>::instr_i64const, 1),
+            version: 1520,
             i64load: cost_instr::<T>(W::<T>::instr_i64load, 0),
             i32load: cost_instr::<T>(W::<T>::instr_i32load, 0),
             i64store: cost_instr::<T>(W::<T>::instr_i64store, 1),
@@ -910,15 +1383,44 @@ impl<T: Config> Default for InstructionWeights<T> {
             i32rotl: cost_instr::<T>(W::<T>::instr_i32rotl, 2),
             i64rotr: cost_instr::<T>(W::<T>::instr_i64rotr, 2),
             i32rotr: cost_instr::<T>(W::<T>::instr_i32rotr, 2),
+            memory_copy: cost_instr::<T>(W::<T>::instr_memory_copy, 3),
+            memory_copy_per_byte: cost_instr::<T>(W::<T>::instr_memory_copy_per_byte, 0),
+            memory_fill: cost_instr::<T>(W::<T>::instr_memory_fill, 3),
+            memory_fill_per_byte: cost_instr::<T>(W::<T>::instr_memory_fill_per_byte, 0),
+            memory_init: cost_instr::<T>(W::<T>::instr_memory_init, 3),
+            memory_init_per_byte: cost_instr::<T>(W::<T>::instr_memory_init_per_byte, 0),
+            data_drop: cost_instr::<T>(W::<T>::instr_data_drop, 0),
+            table_copy: cost_instr::<T>(W::<T>::instr_table_copy, 2),
+            table_init: cost_instr::<T>(W::<T>::instr_table_init, 2),
+            table_grow: cost_instr::<T>(W::<T>::instr_table_grow, 1),
+            table_fill: cost_instr::<T>(W::<T>::instr_table_fill, 2),
+            table_size: cost_instr::<T>(W::<T>::instr_table_size, 0),
+            ref_null: cost_instr::<T>(W::<T>::instr_ref_null, 0),
+            ref_is_null: cost_instr::<T>(W::<T>::instr_ref_is_null, 1),
+            ref_func: cost_instr::<T>(W::<T>::instr_ref_func, 0),
             _phantom: PhantomData,
         }
     }
 }
 
+/// # Note
+///
+/// [`SyscallWeights`] fields carry a real measured `proof_size` (see [`cost`]/[`cost_byte`] and
+/// friends), but [`SyscallCosts`] itself only has single-dimension fields today, so `.ref_time()`
+/// is all that makes it across this conversion. Charging storage-touching syscalls like
+/// `gr_read`, `gr_send` and `gr_create_program` against a PoV budget means growing `SyscallCosts`
+/// (and gas charging around it) in `core-processor` to carry the second dimension, then widening
+/// this conversion to populate it.
+///
+/// [`SyscallWeights::gr_debug_validate_per_byte`] also has no counterpart here: the charge
+/// always goes through [`SyscallCosts::gr_debug_per_byte`] regardless of
+/// [`Limits::gr_debug_enabled`] (see that field's doc), so the validate-only cost is informational
+/// only and isn't threaded through this conversion.
 impl<T: Config> From<SyscallWeights<T>> for SyscallCosts {
     fn from(weights: SyscallWeights<T>) -> SyscallCosts {
         SyscallCosts {
             alloc: weights.alloc.ref_time().into(),
+            alloc_per_page: weights.alloc_per_page.ref_time().into(),
             free: weights.free.ref_time().into(),
             free_range: weights.free_range.ref_time().into(),
             free_range_per_page: weights.free_range_per_page.ref_time().into(),
@@ -1044,7 +1546,10 @@ impl<T: Config> Default for SyscallWeights<T> {
             gr_reply_push_input: cost_batched(W::<T>::gr_reply_push_input),
             gr_reply_push_input_per_byte: cost_byte(W::<T>::gr_reply_push_input_per_kb),
 
+            // Linear regression over the number of pages grown, matching `free_range`'s own
+            // per-page derivation below.
             alloc: cost_batched(W::<T>::alloc),
+            alloc_per_page: cost_batched(W::<T>::alloc_per_page),
             free: cost_batched(W::<T>::free),
             free_range: cost_batched(W::<T>::free_range),
             free_range_per_page: cost_batched(W::<T>::free_range_per_page),
@@ -1067,6 +1572,7 @@ impl<T: Config> Default for SyscallWeights<T> {
             gr_random: cost_batched(W::<T>::gr_random),
             gr_debug: cost_batched(W::<T>::gr_debug),
             gr_debug_per_byte: cost_byte_batched(W::<T>::gr_debug_per_kb),
+            gr_debug_validate_per_byte: cost_byte_batched(W::<T>::gr_debug_validate_per_kb),
             gr_reply_to: cost_batched(W::<T>::gr_reply_to),
             gr_signal_code: cost_batched(W::<T>::gr_signal_code),
             gr_signal_from: cost_batched(W::<T>::gr_signal_from),
@@ -1177,26 +1683,340 @@ impl<T: Config> Default for MemoryWeights<T> {
 
 struct ScheduleRules<'a, T: Config> {
     schedule: &'a Schedule<T>,
-    params: Vec<u32>,
+    // `CallIndirect`'s param-count table: the only per-module, per-instruction-cost-call state
+    // `ScheduleRules` carries (everything else is read straight out of `schedule` by reference,
+    // which already acts as the precomputed per-opcode cost table — it's built once, at
+    // `Schedule` construction, not recomputed per instruction). Borrowed when the caller reuses
+    // a buffer across many modules via [`Schedule::rules_with_buf`], owned when built ad hoc by
+    // [`Schedule::rules`].
+    params: Cow<'a, [u32]>,
 }
 
 impl<T: Config> Schedule<T> {
+    /// Whether code instrumented at `instrumented_version` against `instrumented_backend` needs
+    /// to be re-instrumented against this schedule.
+    ///
+    /// Mirrors the version check described on [`InstructionWeights::version`], extended to also
+    /// retrigger on a [`VMBackend`] change: switching backend changes which [`Limits`] apply
+    /// (see [`Limits::for_backend`]) even when no individual instruction weight moved, so a bare
+    /// version match is not enough to prove the stored code is still valid to run as-is.
+    pub fn needs_reinstrumentation(
+        &self,
+        instrumented_version: u32,
+        instrumented_backend: VMBackend,
+    ) -> bool {
+        instrumented_version != self.instruction_weights.version
+            || instrumented_backend != self.backend
+    }
+
+    /// Computes how many expired waitlist/dispatch-stash/reservation entries the
+    /// `on_initialize` lazy-deletion pass may drain this block, given `remaining_weight` left
+    /// in the block after mandatory work has already been accounted for.
+    ///
+    /// Only [`AVERAGE_ON_INITIALIZE_RATIO`] of `remaining_weight` is put up for grabs, and the
+    /// count is derived independently for `ref_time` and `proof_size` (taking the smaller of
+    /// the two) so that draining the backlog can never exceed either dimension's share of the
+    /// block. Callers should decode and remove at most this many queued task keys, deferring
+    /// whatever is left to the next block; this naturally returns `0` once the block is already
+    /// too full to fit even a single removal, so normal extrinsics are never starved out.
+    pub fn lazy_deletion_budget(&self, remaining_weight: Weight) -> u32 {
+        let budget = AVERAGE_ON_INITIALIZE_RATIO * remaining_weight;
+        let per_entry = self.task_removal_weight;
+
+        let entries_for = |budget_component: u64, per_entry_component: u64| -> u32 {
+            if per_entry_component == 0 {
+                u32::MAX
+            } else {
+                (budget_component / per_entry_component).min(u64::from(u32::MAX)) as u32
+            }
+        };
+
+        entries_for(budget.ref_time(), per_entry.ref_time())
+            .min(entries_for(budget.proof_size(), per_entry.proof_size()))
+    }
+
+    /// Checks a proposed [`ScheduleOverrides`] against the integrity invariants it must not
+    /// violate before a privileged origin is allowed to commit it on-chain.
+    pub fn validate_overrides(&self, overrides: &ScheduleOverrides) -> Result<(), OverrideError> {
+        overrides.syscall_weights.validate()?;
+
+        if let Some(proposed) = overrides.instruction_weights_version {
+            let current = self.instruction_weights.version;
+            if proposed <= current {
+                return Err(OverrideError::StaleInstructionWeightsVersion { proposed, current });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Layers a validated [`ScheduleOverrides`] over `self`, returning the effective schedule a
+    /// hot-fixed syscall price (or version bump) should be instrumented and charged against.
+    /// Fields left as `None` on `overrides` keep this schedule's compiled value.
+    ///
+    /// Callers are expected to have already rejected the override with
+    /// [`Schedule::validate_overrides`]; this does not re-check invariants itself.
+    pub fn apply_overrides(&self, overrides: &ScheduleOverrides) -> Self {
+        let mut schedule = self.clone();
+
+        overrides.syscall_weights.apply(&mut schedule.syscall_weights);
+        if let Some(version) = overrides.instruction_weights_version {
+            schedule.instruction_weights.version = version;
+        }
+
+        schedule
+    }
+
+    /// Checks every [`InstructionWeights`], [`SyscallWeights`] and [`MemoryWeights`] field
+    /// against `reference`, flagging any whose drift exceeds `tolerances`.
+    ///
+    /// Intended for downstream runtimes to run in their own tests or at genesis, to catch a
+    /// mis-benchmarked or hand-edited schedule before it ships, without panicking on the first
+    /// offending field — every mismatch is collected and returned together.
+    pub fn validate_against(
+        &self,
+        reference: &Schedule<T>,
+        tolerances: SpreadConfig,
+    ) -> Result<(), ScheduleMismatch> {
+        let mut mismatches = Vec::new();
+
+        macro_rules! check {
+            ($measured:expr, $expected:expr, $pct:expr, $field:expr) => {
+                let (measured, expected) = ($measured as u64, $expected as u64);
+                if !within_spread(measured, expected, $pct) {
+                    mismatches.push(FieldMismatch {
+                        field: $field,
+                        measured,
+                        expected,
+                        allowed_pct: $pct,
+                    });
+                }
+            };
+        }
+
+        macro_rules! check_instruction_weights {
+            ($($field:ident),+ $(,)?) => {
+                $(check!(
+                    self.instruction_weights.$field,
+                    reference.instruction_weights.$field,
+                    tolerances.instruction_weights_pct,
+                    stringify!($field)
+                );)+
+            };
+        }
+        check_instruction_weights!(
+            i64load, i32load, i64store, i32store, select, r#if, br, br_if, br_table,
+            br_table_per_entry, call, call_indirect, call_indirect_per_param, call_per_local,
+            local_get, local_set, local_tee, global_get, global_set, memory_current, i64clz,
+            i32clz, i64ctz, i32ctz, i64popcnt, i32popcnt, i64eqz, i32eqz, i32extend8s,
+            i32extend16s, i64extend8s, i64extend16s, i64extend32s, i64extendsi32, i64extendui32,
+            i32wrapi64, i64eq, i32eq, i64ne, i32ne, i64lts, i32lts, i64ltu, i32ltu, i64gts, i32gts,
+            i64gtu, i32gtu, i64les, i32les, i64leu, i32leu, i64ges, i32ges, i64geu, i32geu,
+            i64add, i32add, i64sub, i32sub, i64mul, i32mul, i64divs, i32divs, i64divu, i32divu,
+            i64rems, i32rems, i64remu, i32remu, i64and, i32and, i64or, i32or, i64xor, i32xor,
+            i64shl, i32shl, i64shrs, i32shrs, i64shru, i32shru, i64rotl, i32rotl, i64rotr,
+            i32rotr, memory_copy, memory_copy_per_byte, memory_fill, memory_fill_per_byte,
+            memory_init, memory_init_per_byte, data_drop, table_copy, table_init, table_grow,
+            table_fill, table_size, ref_null, ref_is_null, ref_func,
+        );
+
+        macro_rules! check_syscall_weights {
+            ($($field:ident),+ $(,)?) => {
+                $(check!(
+                    self.syscall_weights.$field.ref_time(),
+                    reference.syscall_weights.$field.ref_time(),
+                    tolerances.syscall_weights_pct,
+                    stringify!($field)
+                );)+
+            };
+        }
+        check_syscall_weights!(
+            alloc,
+            alloc_per_page,
+            free,
+            free_range,
+            free_range_per_page,
+            gr_reserve_gas,
+            gr_unreserve_gas,
+            gr_system_reserve_gas,
+            gr_gas_available,
+            gr_message_id,
+            gr_program_id,
+            gr_source,
+            gr_value,
+            gr_value_available,
+            gr_size,
+            gr_read,
+            gr_read_per_byte,
+            gr_env_vars,
+            gr_block_height,
+            gr_block_timestamp,
+            gr_random,
+            gr_reply_deposit,
+            gr_send,
+            gr_send_per_byte,
+            gr_send_wgas,
+            gr_send_wgas_per_byte,
+            gr_send_init,
+            gr_send_push,
+            gr_send_push_per_byte,
+            gr_send_commit,
+            gr_send_commit_wgas,
+            gr_reservation_send,
+            gr_reservation_send_per_byte,
+            gr_reservation_send_commit,
+            gr_reply_commit,
+            gr_reply_commit_wgas,
+            gr_reservation_reply,
+            gr_reservation_reply_per_byte,
+            gr_reservation_reply_commit,
+            gr_reply_push,
+            gr_reply,
+            gr_reply_per_byte,
+            gr_reply_wgas,
+            gr_reply_wgas_per_byte,
+            gr_reply_push_per_byte,
+            gr_reply_to,
+            gr_signal_code,
+            gr_signal_from,
+            gr_reply_input,
+            gr_reply_input_wgas,
+            gr_reply_push_input,
+            gr_reply_push_input_per_byte,
+            gr_send_input,
+            gr_send_input_wgas,
+            gr_send_push_input,
+            gr_send_push_input_per_byte,
+            gr_debug,
+            gr_debug_per_byte,
+            gr_debug_validate_per_byte,
+            gr_reply_code,
+            gr_exit,
+            gr_leave,
+            gr_wait,
+            gr_wait_for,
+            gr_wait_up_to,
+            gr_wake,
+            gr_create_program,
+            gr_create_program_payload_per_byte,
+            gr_create_program_salt_per_byte,
+            gr_create_program_wgas,
+            gr_create_program_wgas_payload_per_byte,
+            gr_create_program_wgas_salt_per_byte,
+        );
+
+        macro_rules! check_memory_weights {
+            ($($field:ident),+ $(,)?) => {
+                $(check!(
+                    self.memory_weights.$field.ref_time(),
+                    reference.memory_weights.$field.ref_time(),
+                    tolerances.memory_weights_pct,
+                    stringify!($field)
+                );)+
+            };
+        }
+        check_memory_weights!(
+            lazy_pages_signal_read,
+            lazy_pages_signal_write,
+            lazy_pages_signal_write_after_read,
+            lazy_pages_host_func_read,
+            lazy_pages_host_func_write,
+            lazy_pages_host_func_write_after_read,
+            load_page_data,
+            upload_page_data,
+            mem_grow,
+            mem_grow_per_page,
+            parachain_read_heuristic,
+        );
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(ScheduleMismatch(mismatches))
+        }
+    }
+
+    /// Builds the per-instruction cost lookup the gas-metering injector consumes.
+    ///
+    /// # Note
+    ///
+    /// Since [`InstructionWeights::version`] 1510 the injector charges gas per basic block
+    /// (a maximal straight-line run ending at a branch, `br_table`, `call`/`call_indirect`,
+    /// `return`, or a block/loop boundary) rather than before every instruction: it sums the
+    /// per-instruction costs returned here — including the `br_table_per_entry` and
+    /// `call_per_local` contributions folded into [`Rules::instruction_cost`] and
+    /// [`Rules::call_per_local_cost`] — once per block at instrumentation time and emits a
+    /// single charge at block entry, with extra metering points re-inserted at every loop
+    /// header and immediately after each call returns so an unbounded loop still traps on gas
+    /// exhaustion. Charging the full block cost up front is a deliberate over-charge when a
+    /// trap occurs mid-block; that injector lives in the `gear-wasm-instrument` crate, outside
+    /// this one, which only supplies the costs the per-block sum is computed from.
     pub fn rules(&self, module: &Module) -> impl Rules + '_ {
         ScheduleRules {
             schedule: self,
-            params: module
-                .type_section()
-                .iter()
-                .flat_map(|section| section.types())
-                .map(|func| {
-                    let Type::Function(func) = func;
-                    func.params().len() as u32
-                })
-                .collect(),
+            params: Cow::Owned(Self::call_indirect_params(module)),
         }
     }
 
+    /// Like [`Self::rules`], but fills `params_buf` instead of allocating a fresh `Vec` for the
+    /// `CallIndirect` param-count table.
+    ///
+    /// Instrumenting many modules back to back (e.g. re-instrumenting an entire chain's worth of
+    /// uploaded code, or a benchmark that runs over thousands of modules) otherwise pays one
+    /// heap allocation and one `Vec` drop per module for this table alone; reusing the same
+    /// buffer turns that into a handful of reallocations that settle at the largest type section
+    /// seen. The returned [`Rules`] impl borrows both `self` and `params_buf`, so it cannot
+    /// outlive either.
+    pub fn rules_with_buf<'a>(
+        &'a self,
+        module: &Module,
+        params_buf: &'a mut Vec<u32>,
+    ) -> impl Rules + 'a {
+        params_buf.clear();
+        params_buf.extend(Self::call_indirect_params(module));
+
+        ScheduleRules {
+            schedule: self,
+            params: Cow::Borrowed(params_buf.as_slice()),
+        }
+    }
+
+    /// The `CallIndirect` param-count table used by [`ScheduleRules::instruction_cost`]: for each
+    /// function type declared in `module`'s type section, the number of parameters it takes.
+    fn call_indirect_params(module: &Module) -> Vec<u32> {
+        module
+            .type_section()
+            .iter()
+            .flat_map(|section| section.types())
+            .map(|func| {
+                let Type::Function(func) = func;
+                func.params().len() as u32
+            })
+            .collect()
+    }
+
+    /// # Note
+    ///
+    /// `db_read_per_byte`/`db_write_per_byte` and the other fields below now carry a real
+    /// measured `proof_size`, but [`ProcessCosts`]' fields are single dimension, so only
+    /// `.ref_time()` survives into it — the same cross-crate gap noted on the
+    /// `SyscallWeights`/`SyscallCosts` and `InstantiationWeights`/`InstantiationCosts`
+    /// conversions above.
+    ///
+    /// When [`Self::memory_grow_cost_strategy`] is [`MemoryGrowCostStrategy::InstructionMetered`],
+    /// `memory.grow` is already charged at instrumentation time (see
+    /// [`ScheduleRules::memory_grow_cost`]), so `ext.mem_grow`/`ext.mem_grow_per_page` are zeroed
+    /// here to keep the host-metered and instruction-metered strategies from charging the same
+    /// grow twice.
     pub fn process_costs(&self) -> ProcessCosts {
+        let (mem_grow, mem_grow_per_page) = match self.memory_grow_cost_strategy {
+            MemoryGrowCostStrategy::HostMetered => (
+                self.memory_weights.mem_grow.ref_time(),
+                self.memory_weights.mem_grow_per_page.ref_time(),
+            ),
+            MemoryGrowCostStrategy::InstructionMetered => (0, 0),
+        };
+
         ProcessCosts {
             ext: ExtCosts {
                 syscalls: self.syscall_weights.clone().into(),
@@ -1205,8 +2025,8 @@ impl<T: Config> Schedule<T> {
                     dispatch_stash: CostsPerBlockOf::<T>::dispatch_stash().into(),
                     reservation: CostsPerBlockOf::<T>::reservation().into(),
                 },
-                mem_grow: self.memory_weights.mem_grow.ref_time().into(),
-                mem_grow_per_page: self.memory_weights.mem_grow_per_page.ref_time().into(),
+                mem_grow: mem_grow.into(),
+                mem_grow_per_page: mem_grow_per_page.into(),
             },
             lazy_pages: self.memory_weights.clone().into(),
             read: DbWeightOf::<T>::get().reads(1).ref_time().into(),
@@ -1230,7 +2050,7 @@ impl<'a, T: Config> Rules for ScheduleRules<'a, T> {
 
         let weight = match *instruction {
             End | Unreachable | Return | Else | Block(_) | Loop(_) | Nop | Drop => 0,
-            I32Const(_) | I64Const(_) => w.i64const,
+            I32Const(_) | I64Const(_) => w.i64add,
             I32Load(_, _)
             | I32Load8S(_, _)
             | I32Load8U(_, _)
@@ -1328,6 +2148,24 @@ impl<'a, T: Config> Rules for ScheduleRules<'a, T> {
                 I64Extend16S => w.i64extend16s,
                 I64Extend32S => w.i64extend32s,
             },
+            // Bulk-memory and reference-type opcodes. Only the per-call base cost is returned
+            // here: the byte count `memory.copy`/`memory.fill`/`memory.init` operate on is a
+            // runtime stack value, not known at instrumentation time, so their linear
+            // `_per_byte` component (see [`InstructionWeights::memory_copy`] and friends) is
+            // charged by a metered host/loop sequence injected elsewhere rather than folded
+            // into this static per-instruction weight.
+            MemoryCopy => w.memory_copy,
+            MemoryFill => w.memory_fill,
+            MemoryInit(_) => w.memory_init,
+            DataDrop(_) => w.data_drop,
+            TableCopy(_, _) => w.table_copy,
+            TableInit(_, _) => w.table_init,
+            TableGrow(_) => w.table_grow,
+            TableFill(_) => w.table_fill,
+            TableSize(_) => w.table_size,
+            RefNull(_) => w.ref_null,
+            RefIsNull => w.ref_is_null,
+            RefFunc(_) => w.ref_func,
             // Returning None makes the gas instrumentation fail which we intend for
             // unsupported or unknown instructions.
             _ => return None,
@@ -1336,7 +2174,13 @@ impl<'a, T: Config> Rules for ScheduleRules<'a, T> {
     }
 
     fn memory_grow_cost(&self) -> MemoryGrowCost {
-        MemoryGrowCost::Free
+        match self.schedule.memory_grow_cost_strategy {
+            MemoryGrowCostStrategy::HostMetered => MemoryGrowCost::Free,
+            MemoryGrowCostStrategy::InstructionMetered => {
+                let per_page = self.schedule.memory_weights.mem_grow_per_page.ref_time();
+                MemoryGrowCost::Linear(per_page.try_into().unwrap_or(u32::MAX))
+            }
+        }
     }
 
     fn call_per_local_cost(&self) -> u32 {
@@ -1467,6 +2311,18 @@ mod test {
             I64Rotl,
             I32Rotr,
             I64Rotr,
+            MemoryCopy,
+            MemoryFill,
+            MemoryInit(0),
+            DataDrop(0),
+            TableCopy(0, 0),
+            TableInit(0, 0),
+            TableGrow(0),
+            TableFill(0),
+            TableSize(0),
+            RefNull(0),
+            RefIsNull,
+            RefFunc(0),
         ]
     }
 
@@ -1517,6 +2373,59 @@ mod test {
         })
     }
 
+    #[test]
+    fn memory_grow_cost_strategies() {
+        let mut schedule = Schedule::<Test>::default();
+        schedule.memory_grow_cost_strategy = MemoryGrowCostStrategy::HostMetered;
+        assert_eq!(
+            schedule.rules(&default_wasm_module()).memory_grow_cost(),
+            MemoryGrowCost::Free
+        );
+
+        schedule.memory_grow_cost_strategy = MemoryGrowCostStrategy::InstructionMetered;
+        assert_eq!(
+            schedule.rules(&default_wasm_module()).memory_grow_cost(),
+            MemoryGrowCost::Linear(schedule.memory_weights.mem_grow_per_page.ref_time() as u32)
+        );
+    }
+
+    // Demonstrates the win `Schedule::rules_with_buf` is for: instrumenting many modules with a
+    // reused buffer must cost the same gas as instrumenting each one fresh via `Schedule::rules`,
+    // while only ever growing the params buffer instead of allocating one per module.
+    #[test]
+    fn rules_with_buf_matches_rules_across_many_modules() {
+        use elements::Instruction::CallIndirect;
+
+        let schedule = Schedule::<Test>::default();
+        let mut params_buf = Vec::new();
+
+        // Each declared type has a distinct param count, so the type section (and with it the
+        // `CallIndirect` param-count table rebuilt on every iteration) grows with `type_count`.
+        for type_count in [0usize, 1, 16, 4096] {
+            let types: String = (0..type_count)
+                .map(|i| format!("(type (func{}))", " (param i32)".repeat(i)))
+                .collect();
+            let wat = format!("(module {types})");
+            let module = Module::from_bytes(
+                wabt::Wat2Wasm::new()
+                    .validate(false)
+                    .convert(wat)
+                    .expect("failed to parse module"),
+            )
+            .expect("module instantiation failed");
+
+            for idx in [0u32, type_count as u32, u32::MAX] {
+                let instruction = CallIndirect(idx, 0);
+                assert_eq!(
+                    schedule.rules(&module).instruction_cost(&instruction),
+                    schedule
+                        .rules_with_buf(&module, &mut params_buf)
+                        .instruction_cost(&instruction)
+                );
+            }
+        }
+    }
+
     /// This function creates a program with full of empty
     /// functions, and returns the size of the wasm code.
     fn module_with_full_idx(count: usize) -> usize {
@@ -1566,4 +2475,100 @@ mod test {
         assert_eq!(indexmap.get(max_idx - 1), Some(&"foobar".to_string()));
         assert_eq!(indexmap.len(), 1);
     }
+
+    #[test]
+    fn within_spread_exact_match() {
+        assert!(within_spread(100, 100, 0));
+    }
+
+    #[test]
+    fn within_spread_zero_expected_only_matches_zero_measured() {
+        assert!(within_spread(0, 0, 0));
+        assert!(!within_spread(1, 0, 100));
+    }
+
+    #[test]
+    fn within_spread_accepts_drift_at_the_boundary() {
+        // 10% of 100 is exactly 10, so 110 (and 90) sit right on the boundary.
+        assert!(within_spread(110, 100, 10));
+        assert!(within_spread(90, 100, 10));
+    }
+
+    #[test]
+    fn within_spread_rejects_drift_past_the_boundary() {
+        assert!(!within_spread(111, 100, 10));
+        assert!(!within_spread(89, 100, 10));
+    }
+
+    #[test]
+    fn within_spread_is_symmetric_in_measured_and_expected_direction() {
+        assert_eq!(within_spread(120, 100, 20), within_spread(100, 120, 20));
+    }
+
+    #[test]
+    fn validate_against_identical_schedules_reports_no_mismatches() {
+        let schedule = Schedule::<Test>::default();
+
+        assert_eq!(
+            schedule.validate_against(&schedule, SpreadConfig::default()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn validate_against_flags_a_field_that_drifted_past_tolerance() {
+        let reference = Schedule::<Test>::default();
+        let mut measured = reference.clone();
+        measured.syscall_weights.alloc = Weight::from_parts(
+            reference.syscall_weights.alloc.ref_time().saturating_mul(2) + 1,
+            reference.syscall_weights.alloc.proof_size(),
+        );
+
+        let Err(ScheduleMismatch(mismatches)) =
+            measured.validate_against(&reference, SpreadConfig::default())
+        else {
+            panic!("alloc's ref_time was doubled, which exceeds the default 10% syscall tolerance");
+        };
+
+        assert!(mismatches.iter().any(|m| m.field == "alloc"));
+    }
+
+    #[test]
+    fn validate_against_collects_every_mismatch_instead_of_stopping_at_the_first() {
+        let reference = Schedule::<Test>::default();
+        let mut measured = reference.clone();
+        measured.syscall_weights.alloc = Weight::from_parts(
+            reference.syscall_weights.alloc.ref_time().saturating_mul(2) + 1,
+            reference.syscall_weights.alloc.proof_size(),
+        );
+        measured.syscall_weights.free = Weight::from_parts(
+            reference.syscall_weights.free.ref_time().saturating_mul(2) + 1,
+            reference.syscall_weights.free.proof_size(),
+        );
+
+        let Err(ScheduleMismatch(mismatches)) =
+            measured.validate_against(&reference, SpreadConfig::default())
+        else {
+            panic!("both alloc and free were doubled, which exceeds the default 10% syscall tolerance");
+        };
+
+        assert!(mismatches.iter().any(|m| m.field == "alloc"));
+        assert!(mismatches.iter().any(|m| m.field == "free"));
+    }
+
+    #[test]
+    fn validate_against_respects_a_wider_configured_tolerance() {
+        let reference = Schedule::<Test>::default();
+        let mut measured = reference.clone();
+        measured.syscall_weights.alloc = Weight::from_parts(
+            reference.syscall_weights.alloc.ref_time().saturating_mul(2),
+            reference.syscall_weights.alloc.proof_size(),
+        );
+
+        let tolerances = SpreadConfig {
+            syscall_weights_pct: 1000,
+            ..SpreadConfig::default()
+        };
+        assert_eq!(measured.validate_against(&reference, tolerances), Ok(()));
+    }
 }
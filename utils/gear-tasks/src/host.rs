@@ -24,10 +24,27 @@ use sp_api::{ApiExt, ProvideRuntimeApi};
 use std::{
     collections::HashMap,
     marker::PhantomData,
-    sync::{mpsc, Arc, OnceLock},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
 };
 
+/// Worker count the pool never shrinks below, even when idle.
+const MIN_WORKERS: usize = TASKS_AMOUNT;
+/// Worker count the pool never grows past, regardless of backlog.
+const MAX_WORKERS: usize = TASKS_AMOUNT * 4;
+/// Combined queued + in-flight task count that counts as "backlogged".
+const HIGH_WATER_MARK: usize = TASKS_AMOUNT * 2;
+/// How long the backlog must stay above [`HIGH_WATER_MARK`] before the pool grows.
+const GROW_SUSTAIN: Duration = Duration::from_millis(500);
+/// How long the pool must have nothing queued or in-flight before it shrinks back to
+/// [`MIN_WORKERS`].
+const SHRINK_IDLE_WINDOW: Duration = Duration::from_secs(5);
+
 static RUNNER_TX: OnceLock<mpsc::Sender<TaskInfo>> = OnceLock::new();
+static QUEUE_DEPTH: OnceLock<Arc<AtomicUsize>> = OnceLock::new();
 
 struct TaskInfo {
     pub func_ref: u64,
@@ -42,7 +59,17 @@ sp_externalities::decl_extension! {
 pub struct GearTasksRunner<RA, Block: sp_api::BlockT> {
     runtime_api_provider: Arc<RA>,
     rx: mpsc::Receiver<TaskInfo>,
-    thread_pool: ThreadPool,
+    // Several pools rather than one: `futures_executor::ThreadPool` has no API to add workers to
+    // or drain an existing pool, so growing capacity means standing up an *additional* pool
+    // alongside whatever's already running (and possibly mid-task) rather than tearing the old
+    // one down. See `resize`'s doc comment for why shrinking back to one pool is still safe.
+    thread_pools: Mutex<Vec<ThreadPool>>,
+    next_pool: AtomicUsize,
+    workers: AtomicUsize,
+    queued: Arc<AtomicUsize>,
+    in_flight: Arc<AtomicUsize>,
+    backlogged_since: Option<Instant>,
+    idle_since: Instant,
     _block: PhantomData<Block>,
 }
 
@@ -56,21 +83,34 @@ where
         let (tx, rx) = mpsc::channel();
         let _tx = RUNNER_TX.get_or_init(move || tx);
 
+        let queued = Arc::new(AtomicUsize::new(0));
+        let _queued = QUEUE_DEPTH.get_or_init(|| queued.clone());
+
         log::error!("TX inited");
 
         Self {
             runtime_api_provider: client,
             rx,
-            thread_pool: ThreadPool::builder()
-                .pool_size(TASKS_AMOUNT)
-                .name_prefix("gear-tasks-")
-                .create()
-                .expect("Thread pool creation failed"),
+            thread_pools: Mutex::new(vec![Self::build_pool(MIN_WORKERS)]),
+            next_pool: AtomicUsize::new(0),
+            workers: AtomicUsize::new(MIN_WORKERS),
+            queued,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            backlogged_since: None,
+            idle_since: Instant::now(),
             _block: PhantomData,
         }
     }
 
-    pub async fn run(self) {
+    fn build_pool(size: usize) -> ThreadPool {
+        ThreadPool::builder()
+            .pool_size(size)
+            .name_prefix("gear-tasks-")
+            .create()
+            .expect("Thread pool creation failed")
+    }
+
+    pub async fn run(mut self) {
         log::error!("RUN started");
 
         for TaskInfo {
@@ -79,8 +119,16 @@ where
             rx,
         } in self.rx
         {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+            self.maybe_rescale();
+
             let client = self.runtime_api_provider.clone();
-            self.thread_pool.spawn_ok(async move {
+            let in_flight = self.in_flight.clone();
+            in_flight.fetch_add(1, Ordering::Relaxed);
+
+            let pools = self.thread_pools.lock().expect("thread pool lock poisoned");
+            let pool_idx = self.next_pool.fetch_add(1, Ordering::Relaxed) % pools.len();
+            pools[pool_idx].spawn_ok(async move {
                 let mut runtime_api = client.runtime_api();
                 runtime_api.register_extension(GearTasksContextExt);
                 let block_hash = client.usage_info().chain.best_hash;
@@ -89,11 +137,67 @@ where
                     .execute_task(block_hash, func_ref, payload)
                     .map_err(|e| JoinError::RuntimeApi(e.to_string()));
 
+                in_flight.fetch_sub(1, Ordering::Relaxed);
                 rx.send(res)
                     .expect("`TaskSpawner` dropped before task completion and `join()` on it")
             });
         }
     }
+
+    /// Grows the pool toward [`MAX_WORKERS`] once the backlog has stayed above
+    /// [`HIGH_WATER_MARK`] for [`GROW_SUSTAIN`], and shrinks it back toward [`MIN_WORKERS`] after
+    /// [`SHRINK_IDLE_WINDOW`] with nothing queued or in-flight. Called before every dispatch so
+    /// sustained bursts get more workers without pinning `MAX_WORKERS` threads while idle.
+    fn maybe_rescale(&mut self) {
+        let backlog =
+            self.queued.load(Ordering::Relaxed) + self.in_flight.load(Ordering::Relaxed);
+        let now = Instant::now();
+
+        if backlog > HIGH_WATER_MARK {
+            self.idle_since = now;
+            match self.backlogged_since {
+                Some(since) if now.duration_since(since) >= GROW_SUSTAIN => {
+                    self.resize(MAX_WORKERS);
+                    self.backlogged_since = None;
+                }
+                Some(_) => {}
+                None => self.backlogged_since = Some(now),
+            }
+        } else {
+            self.backlogged_since = None;
+            if backlog > 0 {
+                self.idle_since = now;
+            } else if now.duration_since(self.idle_since) >= SHRINK_IDLE_WINDOW {
+                self.resize(MIN_WORKERS);
+                self.idle_since = now;
+            }
+        }
+    }
+
+    /// Rescales total worker capacity from `current` to `target`.
+    ///
+    /// Growing (`target > current`) stands up an extra pool sized for the shortfall alongside
+    /// whatever's already running, so a future `spawn_ok`'d onto an existing pool but not yet
+    /// picked up by a worker is never abandoned — `futures_executor::ThreadPool` has no API to
+    /// grow or drain a pool in place. Shrinking only ever happens from [`Self::maybe_rescale`]'s
+    /// `backlog == 0` branch, at which point every pool is idle by construction (nothing queued,
+    /// nothing in flight), so it's safe to tear all of them down and rebuild a single pool of
+    /// `target` size there.
+    fn resize(&self, target: usize) {
+        let current = self.workers.load(Ordering::Relaxed);
+        if current == target {
+            return;
+        }
+
+        log::debug!(target: "gear-tasks", "Rescaling gear-tasks pool from {current} to {target} workers");
+        let mut pools = self.thread_pools.lock().expect("thread pool lock poisoned");
+        if target > current {
+            pools.push(Self::build_pool(target - current));
+        } else {
+            *pools = vec![Self::build_pool(target)];
+        }
+        self.workers.store(target, Ordering::Relaxed);
+    }
 }
 
 sp_externalities::decl_extension! {
@@ -115,6 +219,9 @@ impl TaskSpawner {
         let (rx, tx) = mpsc::sync_channel(1);
 
         let runner_tx = RUNNER_TX.get().expect("`GearTasksRunner` is not spawned");
+        if let Some(depth) = QUEUE_DEPTH.get() {
+            depth.fetch_add(1, Ordering::Relaxed);
+        }
         runner_tx
             .send(TaskInfo {
                 func_ref,
@@ -127,6 +234,15 @@ impl TaskSpawner {
         JoinHandle { inner: handle }
     }
 
+    /// Number of tasks sent to the [`GearTasksRunner`] but not yet picked up for dispatch, so
+    /// callers can observe queue saturation.
+    pub fn queue_depth() -> usize {
+        QUEUE_DEPTH
+            .get()
+            .map(|depth| depth.load(Ordering::Relaxed))
+            .unwrap_or(0)
+    }
+
     pub(crate) fn join(&mut self, handle: JoinHandle) -> JoinResult {
         let tx = self
             .tasks
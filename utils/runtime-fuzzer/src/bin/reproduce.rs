@@ -0,0 +1,49 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2023 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Replays a single honggfuzz crash file through the same [`RuntimeFuzzerInput`] path `main.rs`'s
+//! persistent loop feeds, with `log::trace` enabled, so a crash saved under `$HFUZZ_WORKSPACE` can
+//! be turned back into a readable execution trace with one command:
+//!
+//! ```text
+//! cargo run --release --bin reproduce -- $HFUZZ_WORKSPACE/runtime-fuzzer/SAVED.fuzz
+//! ```
+
+use arbitrary::{Arbitrary, Unstructured};
+use runtime_fuzzer::{run, RuntimeFuzzerInput};
+use std::{env, fs, process::ExitCode};
+
+fn main() -> ExitCode {
+    env_logger::Builder::from_default_env()
+        .filter_level(log::LevelFilter::Trace)
+        .init();
+
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("usage: reproduce <crash-file>");
+        return ExitCode::FAILURE;
+    };
+
+    let data = fs::read(&path).unwrap_or_else(|e| panic!("failed to read {path}: {e}"));
+    let mut unstructured = Unstructured::new(&data);
+    let input = RuntimeFuzzerInput::arbitrary(&mut unstructured)
+        .unwrap_or_else(|e| panic!("failed to build a RuntimeFuzzerInput from {path}: {e}"));
+
+    run(input).unwrap_or_else(|e| panic!("replay of {path} failed before execution: {e:?}"));
+
+    ExitCode::SUCCESS
+}
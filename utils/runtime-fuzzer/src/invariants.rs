@@ -0,0 +1,145 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2023 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Post-condition oracle checked after every block the fuzzer drives, so a silent accounting bug
+//! fails the fuzz run on the block it first shows up in rather than relying on it eventually
+//! causing a panic somewhere downstream (or not at all).
+
+use gear_common::{GasTree, Origin};
+use gear_runtime::{GasHandlerOf, MailboxOf, Runtime, WaitlistOf};
+use pallet_balances::Pallet as BalancesPallet;
+
+/// A post-condition that must hold after every block the fuzzer runs.
+///
+/// Implementations panic (including `test_input_id` in the message) rather than returning a
+/// `Result`: a violation means the corpus entry that triggered it is a genuine crash, and
+/// honggfuzz only saves a crashing input, not an `Err` return value.
+pub(crate) trait Invariant {
+    /// Check the invariant against current on-chain state, panicking with `test_input_id` if it
+    /// doesn't hold.
+    fn check(&self, test_input_id: &str);
+}
+
+/// Every built-in invariant the fuzzer checks, run in sequence after each block.
+pub(crate) struct BuiltinInvariants {
+    /// Total balance + gas-tree value (burned fees are not counted) observed right after `initial_sender_balance`
+    /// was set, i.e. before the first `GearCall` of this corpus entry was dispatched.
+    pub(crate) initial_total_value: u128,
+}
+
+impl BuiltinInvariants {
+    /// Snapshot the system's total value as of right now, to later check conservation against.
+    pub(crate) fn new() -> Self {
+        Self {
+            initial_total_value: total_system_value(),
+        }
+    }
+
+    pub(crate) fn check_all(&self, test_input_id: &str) {
+        ValueConservation {
+            expected: self.initial_total_value,
+        }
+        .check(test_input_id);
+        NoCorruptGasNodes.check(test_input_id);
+        NoOrphanedMailboxOrWaitlistEntries.check(test_input_id);
+    }
+}
+
+/// Sum of every account's free + reserved balance, plus whatever value the gas tree is currently
+/// holding on behalf of in-flight messages. A closed system: this total only ever moves between
+/// these two places (or gets burned as a gas fee), it never appears from or vanishes into thin
+/// air.
+fn total_system_value() -> u128 {
+    let accounts_total: u128 = frame_system::Account::<Runtime>::iter()
+        .map(|(account, _)| {
+            let free: u128 = BalancesPallet::<Runtime>::free_balance(&account).into();
+            let reserved: u128 = BalancesPallet::<Runtime>::reserved_balance(&account).into();
+            free + reserved
+        })
+        .sum();
+
+    let gas_tree_total: u128 = GasHandlerOf::<Runtime>::total_supply().unwrap_or_else(|e| {
+        unreachable!("gas tree must always report a total supply, got error: {e:?}")
+    });
+
+    accounts_total + gas_tree_total
+}
+
+/// Total value in the system (balances + gas tree) must equal `expected`, modulo whatever was
+/// burned as a gas fee: burning only ever removes value, it can't create it, so the current total
+/// must never exceed `expected`.
+struct ValueConservation {
+    expected: u128,
+}
+
+impl Invariant for ValueConservation {
+    fn check(&self, test_input_id: &str) {
+        let current = total_system_value();
+        if current > self.expected {
+            panic!(
+                "[{test_input_id}] value conservation violated: total system value grew from \
+                 {} to {current}, but burning gas fees can only ever shrink it",
+                self.expected
+            );
+        }
+    }
+}
+
+/// No gas-tree node may hold a negative or overflowing balance: every node's value must fit in
+/// the tree's own accounting without having underflowed on a spend.
+struct NoCorruptGasNodes;
+
+impl Invariant for NoCorruptGasNodes {
+    fn check(&self, test_input_id: &str) {
+        for (node_id, node) in GasHandlerOf::<Runtime>::iter() {
+            if node.value().is_none() {
+                panic!(
+                    "[{test_input_id}] gas tree node {node_id:?} has no resolvable value, \
+                     indicating a corrupt (over-spent or orphaned) node"
+                );
+            }
+        }
+    }
+}
+
+/// Every mailbox and waitlist entry must be keyed by a message id the gas tree still recognizes:
+/// an entry surviving past its message's gas node being consumed is exactly the kind of dangling
+/// reference that `claim_value`/wake would otherwise silently misbehave on.
+struct NoOrphanedMailboxOrWaitlistEntries;
+
+impl Invariant for NoOrphanedMailboxOrWaitlistEntries {
+    fn check(&self, test_input_id: &str) {
+        for (_account, message_id, _value) in MailboxOf::<Runtime>::iter() {
+            if !GasHandlerOf::<Runtime>::exists(message_id.into_origin()) {
+                panic!(
+                    "[{test_input_id}] mailbox entry for message {message_id:?} has no backing \
+                     gas tree node"
+                );
+            }
+        }
+
+        for (_program_id, message_id, _expiry) in WaitlistOf::<Runtime>::iter() {
+            if !GasHandlerOf::<Runtime>::exists(message_id.into_origin()) {
+                panic!(
+                    "[{test_input_id}] waitlist entry for message {message_id:?} has no backing \
+                     gas tree node"
+                );
+            }
+        }
+    }
+}
@@ -19,19 +19,25 @@
 #![allow(clippy::items_after_test_module)]
 
 mod gear_calls;
+mod invariants;
 mod runtime;
+mod senders;
 #[cfg(test)]
 mod tests;
 mod utils;
 
 use crate::utils::default_fuzzing_config;
 use arbitrary::{Arbitrary, Error, Result, Unstructured};
-use frame_support::pallet_prelude::DispatchResultWithPostInfo;
-use gear_call_gen::{ClaimValueArgs, GearCall, SendMessageArgs, SendReplyArgs, UploadProgramArgs};
+use frame_support::{pallet_prelude::DispatchResultWithPostInfo, weights::Weight};
+use gear_call_gen::{
+    ClaimValueArgs, CreateProgramArgs, GearCall, SendMessageArgs, SendReplyArgs, UploadProgramArgs,
+};
 use gear_calls::GearCalls;
 use gear_core::ids::ProgramId;
 use gear_runtime::{AccountId, Gear, RuntimeOrigin};
+use invariants::BuiltinInvariants;
 use runtime::*;
+use senders::SenderPool;
 use sha1::*;
 use std::{fmt::Debug, ops::RangeInclusive};
 use utils::default_generator_set;
@@ -68,11 +74,40 @@ pub(crate) struct FuzzingConfig {
 }
 
 /// Runs all the fuzz testing internal machinery.
-pub fn run(RuntimeFuzzerInput(data): RuntimeFuzzerInput<'_>) -> Result<()> {
-    run_impl(data).map(|_| ())
+///
+/// One-shot entrypoint: builds a fresh [`new_test_ext`] for this single `input` and tears it down
+/// on return. Fine for a libfuzzer-style harness that gets a new process per input, but it pays
+/// full runtime/genesis setup cost every call — see [`run_in_ext`] for the persistent-mode
+/// alternative the honggfuzz harness (`src/main.rs`) actually drives.
+pub fn run(input: RuntimeFuzzerInput<'_>) -> Result<()> {
+    run_impl(input.0).map(|_| ())
+}
+
+/// Runs the fuzz testing machinery against an externalities built and owned by the caller.
+///
+/// Meant for a persistent honggfuzz loop: build [`new_test_ext`] once before the loop starts and
+/// pass the same `test_ext` to every iteration's `run_in_ext` call, amortizing its setup cost
+/// across the whole corpus instead of paying it per input like [`run`] does. State mutated by one
+/// input is left in `test_ext` for the next, same as a real chain accumulating blocks.
+pub fn run_in_ext(
+    test_ext: &mut sp_io::TestExternalities,
+    RuntimeFuzzerInput(data): RuntimeFuzzerInput<'_>,
+) -> Result<()> {
+    drive_gear_calls(test_ext, data)
 }
 
 fn run_impl(data: &[u8]) -> Result<sp_io::TestExternalities> {
+    let mut test_ext = new_test_ext();
+    drive_gear_calls(&mut test_ext, data)?;
+    Ok(test_ext)
+}
+
+/// Generates a [`GearCalls`] sequence from `data` and drives it to completion against `test_ext`.
+///
+/// Shared by the one-shot [`run_impl`] (fresh `test_ext` per call) and the persistent
+/// [`run_in_ext`] (one `test_ext` reused across many calls) so both pay the same per-input cost
+/// — generating calls from `data` — and only differ in whether the externalities itself is new.
+fn drive_gear_calls(test_ext: &mut sp_io::TestExternalities, data: &[u8]) -> Result<()> {
     log::trace!(
         "New GearCalls generation: random data received {}",
         data.len()
@@ -82,6 +117,99 @@ fn run_impl(data: &[u8]) -> Result<sp_io::TestExternalities> {
 
     let fuzzing_config = default_fuzzing_config();
 
+    // Reserve a quarter of the corpus entry for building the sender pool and picking which pool
+    // member acts on each call; the rest drives `GearCalls` generation exactly as before.
+    let (senders_data, calls_data) = data.split_at(data.len() / 4);
+    let mut senders_unstructured = Unstructured::new(senders_data);
+    let senders = SenderPool::generate(&mut senders_unstructured, &fuzzing_config)?;
+
+    let unstructured = Unstructured::new(calls_data);
+    let generators = default_generator_set(test_input_id.clone());
+    let gear_calls = GearCalls::new(unstructured, generators, senders.prog_ids())?;
+
+    test_ext.execute_with(|| -> Result<()> {
+        senders.fund_all();
+
+        let invariants = BuiltinInvariants::new();
+
+        for gear_call in gear_calls {
+            let gear_call = gear_call?;
+            let sender = senders.choose(&mut senders_unstructured)?;
+            let call_res = execute_gear_call(sender, gear_call, &fuzzing_config);
+            log::info!("Extrinsic result: {call_res:?}");
+            // Run task and message queues with max possible gas limit.
+            run_to_next_block();
+            invariants.check_all(&test_input_id);
+        }
+
+        Ok(())
+    })
+}
+
+/// Build a fresh chain externality, for a persistent honggfuzz loop to reuse across iterations
+/// via [`run_in_ext`] instead of paying `new_test_ext`'s setup cost per input.
+pub fn new_persistent_ext() -> sp_io::TestExternalities {
+    new_test_ext()
+}
+
+/// Runs the fuzz testing machinery in determinism-checking mode.
+///
+/// Motivated by costs like `RuntimeCosts::Alloc(pages)` depending on how many pages a call
+/// touches rather than being a fixed constant: a metering bug there could make gas charged (or
+/// the resulting state) depend on something other than the call's own inputs, which is fatal for
+/// a consensus runtime where every validator must reach the same charge from the same call.
+///
+/// Before each `GearCall`, the externalities are forked in two: one copy executes the call and
+/// drains the queue for real and keeps driving the rest of the corpus entry; the other replays
+/// the identical call from the same starting state purely to compare against. Diverging gas
+/// consumption or storage root between the two panics with `test_input_id`.
+pub fn run_deterministic(input: RuntimeFuzzerInput<'_>) -> Result<()> {
+    drive_gear_calls_deterministic(input.0)
+}
+
+/// One call's outcome, compared bit-for-bit against a replay of the same call from the same
+/// starting externalities by [`drive_gear_calls_deterministic`].
+struct CallOutcome {
+    gas_consumed: Weight,
+    storage_root: Vec<u8>,
+}
+
+fn execute_and_snapshot(
+    test_ext: &mut sp_io::TestExternalities,
+    sender: AccountId,
+    gear_call: GearCall,
+    fuzzing_config: &FuzzingConfig,
+) -> CallOutcome {
+    test_ext.execute_with(|| {
+        let call_res = execute_gear_call(sender, gear_call, fuzzing_config);
+        run_to_next_block();
+
+        let gas_consumed = match &call_res {
+            Ok(post_info) => post_info.actual_weight,
+            Err(err) => err.post_info.actual_weight,
+        }
+        .unwrap_or_default();
+
+        CallOutcome {
+            gas_consumed,
+            storage_root: sp_io::storage_root(Default::default()),
+        }
+    })
+}
+
+fn drive_gear_calls_deterministic(data: &[u8]) -> Result<()> {
+    log::trace!(
+        "New GearCalls generation: random data received {}",
+        data.len()
+    );
+    let test_input_id = get_sha1_string(data);
+    log::trace!(
+        "Generating GearCalls from corpus (determinism mode) - {}",
+        test_input_id
+    );
+
+    let fuzzing_config = default_fuzzing_config();
+
     let sender = runtime::account(runtime::alice());
     let sender_prog_id = ProgramId::from(*<AccountId as AsRef<[u8; 32]>>::as_ref(&sender));
 
@@ -90,33 +218,43 @@ fn run_impl(data: &[u8]) -> Result<sp_io::TestExternalities> {
     let initial_sender_balance =
         unstructured.int_in_range(fuzzing_config.initial_sender_balance.clone())?;
 
-    let generators = default_generator_set(test_input_id);
+    let generators = default_generator_set(test_input_id.clone());
     let gear_calls = GearCalls::new(unstructured, generators, vec![sender_prog_id])?;
 
     let mut test_ext = new_test_ext();
-    test_ext.execute_with(|| -> Result<()> {
-        // Set balance of the `sender`.
-        {
-            set_account_balance(sender.clone(), initial_sender_balance)
-                .unwrap_or_else(|e| unreachable!("Balance update failed: {e:?}"));
-            log::info!(
-                "Current balance of the sender - {}",
-                get_account_balance(&sender)
+    test_ext.execute_with(|| {
+        set_account_balance(sender.clone(), initial_sender_balance)
+            .unwrap_or_else(|e| unreachable!("Balance update failed: {e:?}"));
+    });
+
+    for gear_call in gear_calls {
+        let gear_call = gear_call?;
+        let mut replay_ext = test_ext.clone();
+
+        let canonical = execute_and_snapshot(
+            &mut test_ext,
+            sender.clone(),
+            gear_call.clone(),
+            &fuzzing_config,
+        );
+        let replay = execute_and_snapshot(&mut replay_ext, sender.clone(), gear_call, &fuzzing_config);
+
+        if canonical.gas_consumed != replay.gas_consumed {
+            panic!(
+                "[{test_input_id}] non-deterministic gas consumption: {:?} the first time vs \
+                 {:?} replaying the identical call from the same starting state",
+                canonical.gas_consumed, replay.gas_consumed
             );
         }
-
-        for gear_call in gear_calls {
-            let gear_call = gear_call?;
-            let call_res = execute_gear_call(sender.clone(), gear_call, &fuzzing_config);
-            log::info!("Extrinsic result: {call_res:?}");
-            // Run task and message queues with max possible gas limit.
-            run_to_next_block();
+        if canonical.storage_root != replay.storage_root {
+            panic!(
+                "[{test_input_id}] non-deterministic execution: storage root diverged replaying \
+                 an identical call from the same starting state"
+            );
         }
+    }
 
-        Ok(())
-    })?;
-
-    Ok(test_ext)
+    Ok(())
 }
 
 fn execute_gear_call(
@@ -177,6 +315,20 @@ fn execute_gear_call(
             let ClaimValueArgs(message_id) = args;
             Gear::claim_value(RuntimeOrigin::signed(sender), message_id)
         }
+        GearCall::CreateProgram(args) => {
+            let CreateProgramArgs((code_id, salt, payload, gas_limit, value)) = args;
+
+            let value = allowed_to_spend_value.min(value);
+
+            Gear::create_program(
+                RuntimeOrigin::signed(sender),
+                code_id,
+                salt,
+                payload,
+                gas_limit,
+                value,
+            )
+        }
         _ => unimplemented!("Unsupported currently."),
     }
 }
@@ -0,0 +1,48 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2023 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Persistent honggfuzz harness.
+//!
+//! `cargo hfuzz run runtime-fuzzer` (or `run-debug`) drives this binary, feeding it inputs from
+//! `$HFUZZ_WORKSPACE/input` and writing anything that panics out under `$HFUZZ_WORKSPACE` named by
+//! its sha1, the same id `get_sha1_string` logs for every generated `GearCalls` sequence — so a
+//! saved crash file and the trace that explains it share one name. Use the companion
+//! `reproduce` binary to replay a saved crash file with `log::trace` turned on.
+//!
+//! `test_ext` is built once, before the loop, and reused for every iteration via
+//! [`runtime_fuzzer::run_in_ext`] so persistent mode actually avoids paying full runtime/genesis
+//! setup per input.
+
+use arbitrary::{Arbitrary, Unstructured};
+use honggfuzz::fuzz;
+use runtime_fuzzer::{new_persistent_ext, run_in_ext, RuntimeFuzzerInput};
+
+fn main() {
+    let mut test_ext = new_persistent_ext();
+
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut unstructured = Unstructured::new(data);
+            let Ok(input) = RuntimeFuzzerInput::arbitrary(&mut unstructured) else {
+                return;
+            };
+
+            let _ = run_in_ext(&mut test_ext, input);
+        });
+    }
+}
@@ -0,0 +1,115 @@
+// This file is part of Gear.
+
+// Copyright (C) 2021-2023 Gear Technologies Inc.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A pool of funded accounts a corpus entry can dispatch `GearCall`s from, instead of every call
+//! coming from the same single `alice()` sender.
+
+use crate::{
+    runtime::{account, alice, get_account_balance, set_account_balance},
+    FuzzingConfig,
+};
+use arbitrary::{Result, Unstructured};
+use gear_core::ids::ProgramId;
+use gear_runtime::AccountId;
+
+/// Upper bound on how many senders a single corpus entry can draw, so a tiny or adversarial input
+/// can't force an unbounded amount of setup work before a single `GearCall` is even dispatched.
+const MAX_SENDERS: usize = 8;
+
+/// A deterministic, distinct account for pool slot `index`.
+///
+/// Slot `0` is always the well-known `alice()` dev account, so a corpus entry that draws a pool
+/// of one sender behaves exactly like the single-sender harness used to.
+fn sender_account(index: usize) -> AccountId {
+    if index == 0 {
+        return account(alice());
+    }
+
+    let mut seed = [0u8; 32];
+    seed[..8].copy_from_slice(&(index as u64).to_le_bytes());
+    AccountId::from(seed)
+}
+
+/// One funded account a `GearCall` can be dispatched from.
+struct Sender {
+    account: AccountId,
+    prog_id: ProgramId,
+    initial_balance: u128,
+}
+
+/// The pool of senders available to a corpus entry, each independently balanced and selectable
+/// per call.
+pub(crate) struct SenderPool {
+    senders: Vec<Sender>,
+}
+
+impl SenderPool {
+    /// Draw a pool of between 1 and [`MAX_SENDERS`] accounts from `unstructured`, each with its
+    /// own `initial_sender_balance` sampled independently from `fuzzing_config`.
+    pub(crate) fn generate(
+        unstructured: &mut Unstructured,
+        fuzzing_config: &FuzzingConfig,
+    ) -> Result<Self> {
+        let pool_size = unstructured.int_in_range(1..=MAX_SENDERS)?;
+
+        let senders = (0..pool_size)
+            .map(|index| {
+                let account = sender_account(index);
+                let prog_id = ProgramId::from(*<AccountId as AsRef<[u8; 32]>>::as_ref(&account));
+                let initial_balance =
+                    unstructured.int_in_range(fuzzing_config.initial_sender_balance.clone())?;
+
+                Ok(Sender {
+                    account,
+                    prog_id,
+                    initial_balance,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { senders })
+    }
+
+    /// The program id every sender in the pool is addressable as, for [`gear_calls::GearCalls`]
+    /// to pick targets from alongside whatever it uploads during the run.
+    ///
+    /// [`gear_calls::GearCalls`]: crate::gear_calls::GearCalls
+    pub(crate) fn prog_ids(&self) -> Vec<ProgramId> {
+        self.senders.iter().map(|sender| sender.prog_id).collect()
+    }
+
+    /// Credit every sender with its drawn `initial_sender_balance`. Must be called from inside
+    /// `test_ext.execute_with`.
+    pub(crate) fn fund_all(&self) {
+        for sender in &self.senders {
+            set_account_balance(sender.account.clone(), sender.initial_balance)
+                .unwrap_or_else(|e| unreachable!("Balance update failed: {e:?}"));
+            log::info!(
+                "Current balance of sender {:?} - {}",
+                sender.account,
+                get_account_balance(&sender.account)
+            );
+        }
+    }
+
+    /// Pick which pool member acts on the next `GearCall`.
+    pub(crate) fn choose(&self, unstructured: &mut Unstructured) -> Result<AccountId> {
+        let index = unstructured.int_in_range(0..=self.senders.len() - 1)?;
+        Ok(self.senders[index].account.clone())
+    }
+}
@@ -45,22 +45,42 @@ const HASH_WORDS_COUNT: usize = mem::size_of::<Hash>() / mem::size_of::<i32>();
 ///
 /// This config will not work for [`PtrType::BufferStart`].
 #[derive(Debug, Clone)]
-pub struct PtrParamFillersConfig(HashMap<Ptr, PtrParamFiller>);
+pub struct PtrParamFillersConfig {
+    fillers: HashMap<Ptr, PtrParamFiller>,
+    /// Previously observed ids for a given [`HashType`], fed back in by the fuzzing harness as it
+    /// discovers them. When non-empty for a hash being generated, [`PtrParamDataGenerator::generate`]
+    /// sometimes reuses one of these instead of fabricating a fresh, almost certainly unknown id —
+    /// so generated wasms are more likely to reference entities (programs, reservations, ...) that
+    /// actually exist, rather than missing every lookup against one made up on the spot.
+    hash_pool: HashMap<HashType, Vec<Hash>>,
+    /// Ceiling a [`ValueRangeStrategy::GasScaled`] range's upper bound is derived from — see
+    /// [`Self::with_gas_budget`].
+    gas_budget: u128,
+}
+
+/// Default value range used where the caller hasn't opted into gas-aware ranging, matching the
+/// flat ceiling this config used before [`ValueRangeStrategy`] existed.
+const DEFAULT_VALUE_RANGE: RangeInclusive<u128> = 0..=100_000_000_000;
 
 impl Default for PtrParamFillersConfig {
     fn default() -> PtrParamFillersConfig {
         let mut this = Self::empty();
-        this.set_rule(PtrParamDataGenerator::Value(0..=100_000_000_000));
+        this.set_rule(PtrParamDataGenerator::Value(ValueRangeStrategy::Fixed(
+            DEFAULT_VALUE_RANGE,
+        )));
         for ty in HashType::all() {
             this.set_rule(PtrParamDataGenerator::HashWithValue {
                 ty,
-                value: 0..=100_000_000_000,
+                value: ValueRangeStrategy::Fixed(DEFAULT_VALUE_RANGE),
+                hash: HashGenKind::Random,
             });
         }
         this.set_rule(PtrParamDataGenerator::TwoHashesWithValue {
             ty1: HashType::ReservationId,
             ty2: HashType::ActorId,
-            value: 0..=100_000_000_000,
+            value: ValueRangeStrategy::Fixed(DEFAULT_VALUE_RANGE),
+            hash1: HashGenKind::Random,
+            hash2: HashGenKind::Random,
         });
 
         this
@@ -69,7 +89,27 @@ impl Default for PtrParamFillersConfig {
 
 impl PtrParamFillersConfig {
     pub fn empty() -> PtrParamFillersConfig {
-        PtrParamFillersConfig(HashMap::new())
+        PtrParamFillersConfig {
+            fillers: HashMap::new(),
+            hash_pool: HashMap::new(),
+            gas_budget: *DEFAULT_VALUE_RANGE.end(),
+        }
+    }
+
+    /// Feeds `ids` into the known-id corpus for `ty`, so future `Hash`/`HashWithValue`/
+    /// `TwoHashes(WithValue)` fillers for that type have a chance to reuse a real entity instead
+    /// of generating bytes that almost certainly match nothing on-chain.
+    pub fn with_hash_pool(mut self, ty: HashType, ids: Vec<Hash>) -> Self {
+        self.hash_pool.entry(ty).or_default().extend(ids);
+        self
+    }
+
+    /// Sets the ceiling [`ValueRangeStrategy::GasScaled`] ranges are derived from, e.g. the
+    /// program's available balance, so fuzzer-generated transfers stay affordable instead of
+    /// always tripping an insufficient-funds rejection.
+    pub fn with_gas_budget(mut self, gas_budget: u128) -> Self {
+        self.gas_budget = gas_budget;
+        self
     }
 
     /// Set the `PointerWrite`s for the specified pointer type.
@@ -96,7 +136,14 @@ impl PtrParamFillersConfig {
                     ptr_data,
                 }
             },
-            Hash(_) | TwoHashes(_, _) => todo!("Currently unsupported defining ptr param filler config for `Hash` and `TwoHashes`."),
+            Hash(_) | TwoHashes(_, _) => {
+                // No value region follows a bare hash, so nothing beyond the hash words
+                // themselves is ever overwritten at an offset.
+                PtrParamFiller {
+                    value_offset: 0,
+                    ptr_data,
+                }
+            },
             BlockNumber
             | BlockTimestamp
             | SizedBufferStart { .. }
@@ -119,11 +166,24 @@ impl PtrParamFillersConfig {
             | MutTwoHashesWithValue(_, _) => panic!("Mutable pointers values are set by executor, not by wasm itself."),
         };
 
-        self.0.insert(ptr, filler);
+        self.fillers.insert(ptr, filler);
     }
 
     pub fn get_rule(&self, ptr: Ptr) -> Option<PtrParamFiller> {
-        self.0.get(&ptr).cloned()
+        self.fillers.get(&ptr).cloned()
+    }
+
+    /// Looks up the rule for `ptr` and generates its data, drawing hashes from [`Self::hash_pool`]
+    /// and resolving any [`ValueRangeStrategy`] against [`Self::gas_budget`]. `None` if no rule is
+    /// set for `ptr`.
+    pub fn generate(&self, ptr: Ptr, unstructured: &mut Unstructured) -> Result<Option<Vec<i32>>> {
+        self.get_rule(ptr)
+            .map(|filler| {
+                filler
+                    .ptr_data
+                    .generate(unstructured, &self.hash_pool, self.gas_budget)
+            })
+            .transpose()
     }
 }
 
@@ -141,44 +201,142 @@ pub(crate) struct PtrParamFiller {
     pub(crate) ptr_data: PtrParamDataGenerator,
 }
 
+/// How the raw bytes for a generated [`Hash`] are produced.
+#[derive(Debug, Clone)]
+pub enum HashGenKind {
+    /// All-zero hash, e.g. for a destination that's deliberately unaddressable.
+    Zeroed,
+    /// `size_of::<Hash>()` bytes of fuzzer-chosen randomness.
+    Random,
+}
+
+impl HashGenKind {
+    fn generate_bytes(&self, unstructured: &mut Unstructured) -> Result<[u8; mem::size_of::<Hash>()]> {
+        match self {
+            Self::Zeroed => Ok([0; mem::size_of::<Hash>()]),
+            Self::Random => {
+                let mut bytes = [0; mem::size_of::<Hash>()];
+                unstructured.fill_buffer(&mut bytes)?;
+                Ok(bytes)
+            }
+        }
+    }
+}
+
 /// Config for values being written into the pointer address. The
 /// actual data can be generated by calling
 /// [`PointerWriteData::generate_data_to_write`].
 #[derive(Debug, Clone)]
 pub enum PtrParamDataGenerator {
-    Value(RangeInclusive<u128>),
+    Value(ValueRangeStrategy),
     HashWithValue {
         ty: HashType,
-        value: RangeInclusive<u128>,
-        // TODO: add todo for hash data.
-        // hash: [u8; 32]
+        value: ValueRangeStrategy,
+        hash: HashGenKind,
     },
     TwoHashesWithValue {
         ty1: HashType,
         ty2: HashType,
-        value: RangeInclusive<u128>,
-        // hash1: [u8; 32]
-        // hash2: [u8; 32]
+        value: ValueRangeStrategy,
+        hash1: HashGenKind,
+        hash2: HashGenKind,
+    },
+    Hash {
+        ty: HashType,
+        hash: HashGenKind,
+    },
+    TwoHashes {
+        ty1: HashType,
+        ty2: HashType,
+        hash1: HashGenKind,
+        hash2: HashGenKind,
     },
 }
 
 impl PtrParamDataGenerator {
-    /// Get the actual data that should be written into the memory.
-    pub fn generate(&self, unstructured: &mut Unstructured) -> Result<Vec<i32>> {
+    /// Get the actual data that should be written into the memory. `hash_pool` is consulted for
+    /// every hash generated — see [`PtrParamFillersConfig::hash_pool`] — and `gas_budget` for
+    /// every [`ValueRangeStrategy::GasScaled`] range resolved.
+    pub fn generate(
+        &self,
+        unstructured: &mut Unstructured,
+        hash_pool: &HashMap<HashType, Vec<Hash>>,
+        gas_budget: u128,
+    ) -> Result<Vec<i32>> {
         match self {
-            Self::Value(range) => {
-                let value = unstructured.int_in_range(range.clone())?;
-                Ok(value
-                    .to_le_bytes()
-                    .chunks(mem::size_of::<u128>() / mem::size_of::<i32>())
-                    .map(|word_bytes| {
-                        i32::from_le_bytes(word_bytes.try_into().expect("Chunks are of the exact size."))
-                    })
-                    .collect())
+            Self::Value(strategy) => Self::value_words(strategy, gas_budget, unstructured),
+            Self::HashWithValue { ty, value, hash } => {
+                let mut words = Self::hash_words(ty.clone(), hash, hash_pool, unstructured)?;
+                words.extend(Self::value_words(value, gas_budget, unstructured)?);
+                Ok(words)
+            }
+            Self::TwoHashesWithValue {
+                ty1,
+                ty2,
+                value,
+                hash1,
+                hash2,
+            } => {
+                let mut words = Self::hash_words(ty1.clone(), hash1, hash_pool, unstructured)?;
+                words.extend(Self::hash_words(ty2.clone(), hash2, hash_pool, unstructured)?);
+                words.extend(Self::value_words(value, gas_budget, unstructured)?);
+                Ok(words)
+            }
+            Self::Hash { ty, hash } => Self::hash_words(ty.clone(), hash, hash_pool, unstructured),
+            Self::TwoHashes {
+                ty1,
+                ty2,
+                hash1,
+                hash2,
+            } => {
+                let mut words = Self::hash_words(ty1.clone(), hash1, hash_pool, unstructured)?;
+                words.extend(Self::hash_words(ty2.clone(), hash2, hash_pool, unstructured)?);
+                Ok(words)
             }
-            _ => todo!("TODO"),
         }
     }
+
+    /// Encodes a [`Hash`] as little-endian `i32` words. Prefers reusing a known id from
+    /// `hash_pool`'s entry for `ty` over `hash`-generated bytes, with the fuzzer's own input
+    /// deciding whether a reuse happens on any given call — that way coverage-guided mutation is
+    /// free to push generation toward referencing real entities once it discovers that pays off.
+    fn hash_words(
+        ty: HashType,
+        hash: &HashGenKind,
+        hash_pool: &HashMap<HashType, Vec<Hash>>,
+        unstructured: &mut Unstructured,
+    ) -> Result<Vec<i32>> {
+        let known_ids = hash_pool.get(&ty).filter(|ids| !ids.is_empty());
+        let bytes = match known_ids {
+            Some(ids) if unstructured.arbitrary()? => *unstructured.choose(ids)?,
+            _ => hash.generate_bytes(unstructured)?,
+        };
+
+        Ok(bytes
+            .chunks(mem::size_of::<i32>())
+            .map(|word_bytes| {
+                i32::from_le_bytes(word_bytes.try_into().expect("Chunks are of the exact size."))
+            })
+            .collect())
+    }
+
+    /// Draws a `u128` from `strategy`'s range (resolved against `gas_budget`) and encodes it as
+    /// little-endian `i32` words.
+    fn value_words(
+        strategy: &ValueRangeStrategy,
+        gas_budget: u128,
+        unstructured: &mut Unstructured,
+    ) -> Result<Vec<i32>> {
+        let range = strategy.resolve(gas_budget);
+        let value = unstructured.int_in_range(range)?;
+        Ok(value
+            .to_le_bytes()
+            .chunks(mem::size_of::<u128>() / mem::size_of::<i32>())
+            .map(|word_bytes| {
+                i32::from_le_bytes(word_bytes.try_into().expect("Chunks are of the exact size."))
+            })
+            .collect())
+    }
 }
 
 impl From<PtrParamDataGenerator> for Ptr {
@@ -189,6 +347,39 @@ impl From<PtrParamDataGenerator> for Ptr {
             PtrParamDataGenerator::TwoHashesWithValue { ty1, ty2, .. } => {
                 Ptr::TwoHashesWithValue(ty1, ty2)
             }
+            PtrParamDataGenerator::Hash { ty, .. } => Ptr::Hash(ty),
+            PtrParamDataGenerator::TwoHashes { ty1, ty2, .. } => Ptr::TwoHashes(ty1, ty2),
+        }
+    }
+}
+
+/// How the upper bound of a generated `value` is derived, so it can track the actual cost of the
+/// syscall consuming it (mirroring parametrized costing like `alloc`'s per-page charge) instead of
+/// every value-bearing pointer sharing one flat range.
+#[derive(Debug, Clone)]
+pub enum ValueRangeStrategy {
+    /// Always draws from this exact range, independent of any gas budget.
+    Fixed(RangeInclusive<u128>),
+    /// Scales with the config's gas budget: the upper bound is `gas_budget / per_unit_cost`
+    /// (or `gas_budget` itself if `per_unit_cost` is zero), clamped to never fall below `lower`.
+    /// Keeps generated transfers affordable against a supplied balance/gas ceiling so
+    /// `send`-family syscalls exercise post-transfer logic instead of always failing on
+    /// insufficient funds.
+    GasScaled { lower: u128, per_unit_cost: u128 },
+}
+
+impl ValueRangeStrategy {
+    fn resolve(&self, gas_budget: u128) -> RangeInclusive<u128> {
+        match self {
+            Self::Fixed(range) => range.clone(),
+            Self::GasScaled { lower, per_unit_cost } => {
+                let upper = if *per_unit_cost == 0 {
+                    gas_budget
+                } else {
+                    gas_budget / per_unit_cost
+                };
+                *lower..=upper.max(*lower)
+            }
         }
     }
 }